@@ -0,0 +1,116 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+use crate::error::{Error, EXIT_USAGE};
+use crate::output::{self, Format, NumberStyle};
+use crate::repl::{line_options, split_args};
+use crate::{Cmd, Outcome};
+
+/// Converts every line of `input`, a file or `-` for stdin, and prints the results as they come.
+///
+/// Lines are written like the command line, e.g. `10 km mi` or `temp 100 c f`. When `units` are
+/// given, they are appended to every line, so that a line holds just the value. Blank lines and
+/// lines starting with `#` are skipped.
+///
+/// A line that fails is reported on stderr with its line number and the run goes on. Returns the
+/// exit code of the first failing line, or 0 when every line was converted. Warnings such as
+/// stale exchange rates go to stderr too, once rather than for every line.
+pub fn run(input: &str, units: &[String], format: Format, style: &NumberStyle) -> Result<i32, Error> {
+    let reader: Box<dyn BufRead> = if input == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        let file = File::open(input).map_err(|e| Error::Io(format!("can't open '{}': {}", input, e)))?;
+        Box::new(BufReader::new(file))
+    };
+
+    if let Some(header) = format.header() {
+        println!("{}", header);
+    }
+
+    let mut exit_code = 0;
+    let mut warned = None;
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| Error::Io(format!("can't read '{}': {}", input, e)))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match convert_line(line, units, format, style) {
+            Ok((output, warning)) => {
+                if let Some(warning) = warning.filter(|warning| warned.as_ref() != Some(warning)) {
+                    eprintln!("Warning: {}", warning);
+                    warned = Some(warning);
+                }
+                println!("{}", output);
+            }
+            Err((code, message)) => {
+                eprintln!("line {}: {}", index + 1, message);
+                if exit_code == 0 {
+                    exit_code = code;
+                }
+            }
+        }
+    }
+
+    Ok(exit_code)
+}
+
+/// The output of a line and its warning, if any
+fn convert_line(
+    line: &str,
+    units: &[String],
+    format: Format,
+    style: &NumberStyle,
+) -> Result<(String, Option<String>), (i32, String)> {
+    let is_value = style.locale().parse_number(line).is_ok();
+    if !units.is_empty() && !is_value {
+        let error = Error::ParseValue(line.to_string());
+        return Err((error.exit_code(), error.to_string()));
+    }
+    // A value alone would otherwise be read as an expression that converts to itself
+    if units.is_empty() && is_value {
+        let error = Error::MissingArgument("From unit");
+        return Err((error.exit_code(), error.to_string()));
+    }
+
+    let args = split_args(line).into_iter().chain(units.iter().cloned());
+    let mut cmd = Cmd::try_parse_args(std::iter::once("cnv".to_string()).chain(args)).map_err(|e| {
+        // Only the first line of clap's message, the usage that follows is for the whole command
+        let message = e.to_string();
+        (EXIT_USAGE, message.lines().next().unwrap_or_default().trim_start_matches("error: ").to_string())
+    })?;
+    let (format, style) = line_options(&cmd, format, style);
+    cmd.style = style.clone();
+
+    let outcome = cmd.execute().map_err(|e| (e.exit_code(), e.to_string()))?;
+    let warning = outcome.warning().map(str::to_string);
+    let output = match outcome {
+        Outcome::Conversion(conversion) => output::render(&conversion, format, &style),
+        Outcome::Table(conversions) => output::render_table(&conversions, format, &style),
+        Outcome::UnitList(list) => list,
+        Outcome::Repl | Outcome::Batch { .. } | Outcome::Csv(_) => {
+            return Err((EXIT_USAGE, "not available in batch mode".to_string()))
+        }
+    };
+    Ok((output, warning))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_lines_with_and_without_units() {
+        let style = NumberStyle::default();
+        let units = ["km".to_string(), "m".to_string()];
+        assert_eq!(convert_line("2", &units, Format::Plain, &style).unwrap().0, "2000");
+        assert_eq!(convert_line("2 km m", &[], Format::Plain, &style).unwrap().0, "2000");
+    }
+
+    #[test]
+    fn rejects_values_without_units() {
+        let style = NumberStyle::default();
+        assert_eq!(convert_line("5", &[], Format::Plain, &style), Err((EXIT_USAGE, "From unit required".to_string())));
+    }
+}
@@ -1,23 +1,35 @@
-use std::{env, error::Error, fs, path::Path};
+use std::{env, error::Error, fs, path::{Path, PathBuf}};
 use dotenv::dotenv;
 
+/// Downloads the latest rates and writes them where a rate provider can serve them from.
+///
+/// The rates come from `CURRENCY_API_URL`, or from currencyapi.com with `CURRENCY_API_KEY`.
+/// They are written to the path given as the first argument, or `CURRENCY_RATES_PATH`, or
+/// `currency_rates.json` in the home directory.
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>>{
-  dotenv()?;
-  let currency_api_key = env::var("CURRENCY_API_KEY")?;
-  let home_dir = env::var("HOME")?;
+  dotenv().ok();
 
-  let url = format!("https://api.currencyapi.com/v3/latest?apikey={}", currency_api_key);
-  let response = reqwest::get(url).await?;
+  let url = match env::var("CURRENCY_API_URL") {
+    Ok(url) => url,
+    Err(_) => {
+      let currency_api_key = env::var("CURRENCY_API_KEY")?;
+      format!("https://api.currencyapi.com/v3/latest?apikey={}", currency_api_key)
+    }
+  };
+  let response = reqwest::get(url).await?.error_for_status()?;
   let json_response = response.text().await?;
 
-  let file_path = format!("{}/currency_rates.json", home_dir);
-  if let Some(parent) = Path::new(&file_path).parent() {
+  let file_path = match env::args().nth(1).or_else(|| env::var("CURRENCY_RATES_PATH").ok()) {
+    Some(path) => PathBuf::from(path),
+    None => Path::new(&env::var("HOME")?).join("currency_rates.json"),
+  };
+  if let Some(parent) = file_path.parent() {
     fs::create_dir_all(parent)?
   }
 
-  fs::write(file_path, json_response)?;
-  println!("Currency rates updated!");
+  fs::write(&file_path, json_response)?;
+  println!("Currency rates written to {}", file_path.display());
 
   Ok(())
-}
\ No newline at end of file
+}
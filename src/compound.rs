@@ -0,0 +1,364 @@
+use std::fmt;
+
+use crate::error::Error;
+use crate::fuzzy;
+use crate::registry::registry;
+
+const SYMBOLS: [&str; 8] = ["m", "kg", "s", "A", "K", "mol", "cd", "bit"];
+
+/// Exponents of the SI base dimensions (length, mass, time, electric current,
+/// temperature, amount of substance, luminous intensity) plus data in bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BaseDimensions(pub [i8; 8]);
+
+impl BaseDimensions {
+    pub const NONE: Self = BaseDimensions([0, 0, 0, 0, 0, 0, 0, 0]);
+    pub const LENGTH: Self = BaseDimensions([1, 0, 0, 0, 0, 0, 0, 0]);
+    pub const MASS: Self = BaseDimensions([0, 1, 0, 0, 0, 0, 0, 0]);
+    pub const TIME: Self = BaseDimensions([0, 0, 1, 0, 0, 0, 0, 0]);
+    pub const TEMPERATURE: Self = BaseDimensions([0, 0, 0, 0, 1, 0, 0, 0]);
+    pub const DATA: Self = BaseDimensions([0, 0, 0, 0, 0, 0, 0, 1]);
+    pub const AREA: Self = BaseDimensions([2, 0, 0, 0, 0, 0, 0, 0]);
+    pub const VOLUME: Self = BaseDimensions([3, 0, 0, 0, 0, 0, 0, 0]);
+    pub const FREQUENCY: Self = BaseDimensions([0, 0, -1, 0, 0, 0, 0, 0]);
+    pub const SPEED: Self = BaseDimensions([1, 0, -1, 0, 0, 0, 0, 0]);
+    pub const FORCE: Self = BaseDimensions([1, 1, -2, 0, 0, 0, 0, 0]);
+    pub const ENERGY: Self = BaseDimensions([2, 1, -2, 0, 0, 0, 0, 0]);
+    pub const POWER: Self = BaseDimensions([2, 1, -3, 0, 0, 0, 0, 0]);
+    pub const DATA_RATE: Self = BaseDimensions([0, 0, -1, 0, 0, 0, 0, 1]);
+
+    /// The dimensions raised to a power, or `None` if an exponent leaves the range of `i8`
+    pub fn checked_powi(self, exponent: i8) -> Option<Self> {
+        let mut exponents = self.0;
+        for e in exponents.iter_mut() {
+            *e = e.checked_mul(exponent)?;
+        }
+        Some(BaseDimensions(exponents))
+    }
+
+    /// The dimensions of a product, whose exponents are the sums of both, or `None` if one
+    /// leaves the range of `i8`
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        let mut exponents = self.0;
+        for (e, r) in exponents.iter_mut().zip(rhs.0) {
+            *e = e.checked_add(r)?;
+        }
+        Some(BaseDimensions(exponents))
+    }
+
+    /// The dimensions of a quotient, or `None` if an exponent leaves the range of `i8`
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        self.checked_mul(rhs.checked_powi(-1)?)
+    }
+}
+
+impl fmt::Display for BaseDimensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = SYMBOLS
+            .iter()
+            .zip(self.0)
+            .filter(|(_, e)| *e != 0)
+            .map(|(symbol, e)| match e {
+                1 => symbol.to_string(),
+                e => format!("{}^{}", symbol, e),
+            })
+            .collect();
+
+        if parts.is_empty() {
+            write!(f, "dimensionless")
+        } else {
+            write!(f, "{}", parts.join("·"))
+        }
+    }
+}
+
+/// A unit built from registered units with `*`, `/`, `^` and parentheses, e.g. `kg*m/s^2`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompoundUnit {
+    pub expression: String,
+    /// Size of one of this unit in coherent SI units
+    pub factor: f64,
+    pub dimensions: BaseDimensions,
+}
+
+impl CompoundUnit {
+    pub fn parse(expression: &str) -> Result<Self, Error> {
+        let normalized = expression.replace('²', "^2").replace('³', "^3");
+        let tokens = tokenize(&normalized);
+        let mut parser = Parser { tokens: &tokens, position: 0, expression };
+        let (factor, dimensions) = parser.expression()?;
+
+        if let Some(token) = tokens.get(parser.position) {
+            return Err(parser.unexpected(Some(token)));
+        }
+
+        Ok(CompoundUnit {
+            expression: expression.to_string(),
+            factor,
+            dimensions,
+        })
+    }
+
+    /// Converts a value in this unit to `other`, as long as both have the same dimensions
+    pub fn convert(&self, value: f64, other: &CompoundUnit) -> Result<f64, Error> {
+        if self.dimensions != other.dimensions {
+            return Err(Error::DimensionMismatch {
+                from: self.expression.clone(),
+                from_dimensions: self.dimensions.to_string(),
+                to: other.expression.clone(),
+                to_dimensions: other.dimensions.to_string(),
+            });
+        }
+        Ok(value * self.factor / other.factor)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Mul,
+    Div,
+    Pow,
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "{}", word),
+            Token::Mul => write!(f, "*"),
+            Token::Div => write!(f, "/"),
+            Token::Pow => write!(f, "^"),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+
+    for c in expression.chars() {
+        let token = match c {
+            '*' | '·' | '×' => Token::Mul,
+            '/' => Token::Div,
+            '^' => Token::Pow,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            c => {
+                word.push(c);
+                continue;
+            }
+        };
+        if !word.trim().is_empty() {
+            tokens.push(Token::Word(word.trim().to_string()));
+        }
+        word.clear();
+        tokens.push(token);
+    }
+    if !word.trim().is_empty() {
+        tokens.push(Token::Word(word.trim().to_string()));
+    }
+
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    expression: &'a str,
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn error(&self, problem: String) -> Error {
+        Error::InvalidUnit { unit: self.expression.to_string(), problem }
+    }
+
+    /// A token that does not belong where it is, or the end of the unit when it is `None`
+    fn unexpected(&self, token: Option<&Token>) -> Error {
+        match token {
+            Some(token) => self.error(format!("unexpected '{}'", token)),
+            None => self.error("unexpected end".to_string()),
+        }
+    }
+
+    fn overflow(&self) -> Error {
+        self.error("exponents are out of range".to_string())
+    }
+
+    fn expression(&mut self) -> Result<(f64, BaseDimensions), Error> {
+        let (mut factor, mut dimensions) = self.power()?;
+
+        loop {
+            match self.peek() {
+                Some(Token::Mul) => {
+                    self.position += 1;
+                    let (f, d) = self.power()?;
+                    factor *= f;
+                    dimensions = dimensions.checked_mul(d).ok_or_else(|| self.overflow())?;
+                }
+                Some(Token::Div) => {
+                    self.position += 1;
+                    let (f, d) = self.power()?;
+                    factor /= f;
+                    dimensions = dimensions.checked_div(d).ok_or_else(|| self.overflow())?;
+                }
+                _ => return Ok((factor, dimensions)),
+            }
+        }
+    }
+
+    fn power(&mut self) -> Result<(f64, BaseDimensions), Error> {
+        let (factor, dimensions) = self.primary()?;
+
+        if self.peek() != Some(&Token::Pow) {
+            return Ok((factor, dimensions));
+        }
+        self.position += 1;
+
+        let exponent = match self.next().cloned() {
+            Some(Token::Word(word)) => match word.parse::<i8>() {
+                Ok(exponent) => exponent,
+                Err(_) if word.parse::<i64>().is_ok() => return Err(self.overflow()),
+                Err(_) => return Err(self.unexpected(Some(&Token::Word(word)))),
+            },
+            token => return Err(self.unexpected(token.as_ref())),
+        };
+        let dimensions = dimensions.checked_powi(exponent).ok_or_else(|| self.overflow())?;
+        Ok((factor.powi(exponent.into()), dimensions))
+    }
+
+    fn primary(&mut self) -> Result<(f64, BaseDimensions), Error> {
+        match self.next().cloned() {
+            Some(Token::Word(word)) => resolve_word(&word),
+            Some(Token::Open) => {
+                let inner = self.expression()?;
+                match self.next().cloned() {
+                    Some(Token::Close) => Ok(inner),
+                    token => Err(self.unexpected(token.as_ref())),
+                }
+            }
+            token => Err(self.unexpected(token.as_ref())),
+        }
+    }
+}
+
+/// Resolves a unit alias, optionally preceded by a number as in `100km`
+fn resolve_word(word: &str) -> Result<(f64, BaseDimensions), Error> {
+    if let Some(unit) = registry().find(word) {
+        return Ok((unit.si_factor(), unit.base_dimensions()));
+    }
+
+    let split = word
+        .char_indices()
+        .find(|(_, c)| !(c.is_ascii_digit() || *c == '.'))
+        .map_or(word.len(), |(i, _)| i);
+    let (number, rest) = word.split_at(split);
+    if !number.is_empty() {
+        let coefficient: f64 = number.parse().map_err(|_| Error::ParseValue(number.to_string()))?;
+        let rest = rest.trim();
+        if rest.is_empty() {
+            return Ok((coefficient, BaseDimensions::NONE));
+        }
+        let (factor, dimensions) = resolve_word(rest)?;
+        return Ok((coefficient * factor, dimensions));
+    }
+
+    // Units separated by spaces multiply, as in "kW h"
+    if word.contains(char::is_whitespace) {
+        return word.split_whitespace().try_fold((1.0, BaseDimensions::NONE), |(f, d), part| {
+            let (factor, dimensions) = resolve_word(part)?;
+            let dimensions = d.checked_mul(dimensions).ok_or_else(|| Error::InvalidUnit {
+                unit: word.to_string(),
+                problem: "exponents are out of range".to_string(),
+            })?;
+            Ok((f * factor, dimensions))
+        });
+    }
+
+    let aliases = registry().units().flat_map(|unit| unit.aliases.iter().copied());
+    Err(Error::unknown_unit("", word, fuzzy::suggest(aliases, word)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(value: f64, from: &str, to: &str) -> f64 {
+        CompoundUnit::parse(from).unwrap().convert(value, &CompoundUnit::parse(to).unwrap()).unwrap()
+    }
+
+    fn problem(unit: &str) -> String {
+        match CompoundUnit::parse(unit) {
+            Err(Error::InvalidUnit { problem, .. }) => problem,
+            other => panic!("expected an invalid unit for {}, got {:?}", unit, other),
+        }
+    }
+
+    #[test]
+    fn converts_products_and_quotients() {
+        assert!((convert(1.0, "N*m", "J") - 1.0).abs() < 1e-12);
+        assert!((convert(60.0, "km/h", "m/s") - 16.666_666_666_7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn converts_powers_of_units() {
+        assert!((convert(1.0, "g/cm^3", "lb/ft^3") - 62.42796).abs() < 1e-4);
+    }
+
+    #[test]
+    fn reads_temperatures_in_compounds_as_intervals() {
+        assert!((convert(1.0, "BTU/(lb*f)", "J/(kg*K)") - 4186.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reads_dimensions() {
+        assert_eq!(CompoundUnit::parse("kg*m/s^2").unwrap().dimensions, BaseDimensions::FORCE);
+        assert_eq!(CompoundUnit::parse("m²").unwrap().dimensions, BaseDimensions::AREA);
+        assert_eq!(CompoundUnit::parse("(m/s)^2").unwrap().dimensions.0[..3], [2, 0, -2]);
+        assert_eq!(CompoundUnit::parse("kW h").unwrap().dimensions, BaseDimensions::ENERGY);
+    }
+
+    #[test]
+    fn rejects_mismatched_dimensions() {
+        let metre = CompoundUnit::parse("m").unwrap();
+        let second = CompoundUnit::parse("s").unwrap();
+        assert!(matches!(metre.convert(1.0, &second), Err(Error::DimensionMismatch { .. })));
+    }
+
+    #[test]
+    fn names_the_bad_token() {
+        assert_eq!(problem("(m"), "unexpected end");
+        assert_eq!(problem("m/"), "unexpected end");
+        assert_eq!(problem("^2"), "unexpected '^'");
+        assert_eq!(problem("m)"), "unexpected ')'");
+        assert_eq!(problem("m^x"), "unexpected 'x'");
+    }
+
+    #[test]
+    fn rejects_exponents_out_of_range() {
+        assert_eq!(problem("(m^100)^2"), "exponents are out of range");
+        assert_eq!(problem("m^127*m"), "exponents are out of range");
+        assert_eq!(problem("1/m^127/m^2"), "exponents are out of range");
+        assert_eq!(problem("m^999"), "exponents are out of range");
+        assert_eq!(BaseDimensions::LENGTH.checked_powi(127), Some(BaseDimensions([127, 0, 0, 0, 0, 0, 0, 0])));
+        assert_eq!(BaseDimensions([-128, 0, 0, 0, 0, 0, 0, 0]).checked_powi(-1), None);
+    }
+
+    #[test]
+    fn reports_unknown_words() {
+        assert!(matches!(CompoundUnit::parse("furlongz/s"), Err(Error::UnknownUnit { .. })));
+    }
+}
@@ -0,0 +1,29 @@
+//! Settings read from the environment or from cnv's config file
+
+use std::collections::HashMap;
+use std::fs;
+
+use directories::ProjectDirs;
+use once_cell::sync::Lazy;
+
+/// `KEY=value` lines, with `#` starting comments, of the `config` file in cnv's config directory, e.g. `~/.config/cnv/config`
+static FILE: Lazy<HashMap<String, String>> = Lazy::new(|| {
+    let Some(dirs) = ProjectDirs::from("", "", "cnv") else {
+        return HashMap::new();
+    };
+    let Ok(text) = fs::read_to_string(dirs.config_dir().join("config")) else {
+        return HashMap::new();
+    };
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().trim_matches(['"', '\'']).to_string()))
+        .collect()
+});
+
+/// The value of a setting such as `CNV_RATES_PROVIDER`, the environment taking precedence over
+/// the config file
+pub(crate) fn get(key: &str) -> Option<String> {
+    std::env::var(key).ok().or_else(|| FILE.get(key).cloned()).filter(|value| !value.is_empty())
+}
@@ -3,7 +3,7 @@ use std::str::FromStr;
 
 use super::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AreaUnit {
     SquareKilometer,
     SquareMeter,
@@ -39,6 +39,8 @@ pub enum AreaUnit {
     Cent,
 }
 
+pub struct Area;
+
 pub struct UnitDef {
     variant: AreaUnit,
     name: &'static str,
@@ -242,14 +244,14 @@ const UNIT_DEFS: &[UnitDef] = &[
     },
 ];
 
-impl_conversion_traits!(AreaUnit, UNIT_DEFS);
+impl_conversion_traits!(Area, AreaUnit, UNIT_DEFS, "area", "area", BaseDimensions::AREA);
 
 pub fn help_text() -> String {
     AreaUnit::generate_help_text()
 }
 
-impl AreaUnit {
-    fn factor(&self) -> f64 {
+impl Scaled for AreaUnit {
+    fn factor(self) -> f64 {
         use AreaUnit::*;
         
         match self {
@@ -287,9 +289,23 @@ impl AreaUnit {
             Cent => 40.4686,
         }
     }
+
+    fn exact_factor(self) -> Option<Exact> {
+        use AreaUnit::*;
+        // The US survey foot is 1200/3937 m
+        let survey_foot = Exact::fraction(1200 * 1200, 3937 * 3937);
+        match self {
+            SquareFootUSSurvey => Some(survey_foot),
+            AcreUSSurvey => Some(survey_foot.times(Exact::fraction(43560, 1))),
+            Tsubo => Some(Exact::fraction(400, 121)),
+            // Rounded, or depending on π
+            Township | Ping | Killa | Ground | Cent | CircularInch | CircularMil => None,
+            unit => Exact::decimal(unit.factor()),
+        }
+    }
 }
 
-pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, &'static str> {
+pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, Error> {
     let from: AreaUnit = from_unit.parse()?;
     let to: AreaUnit = to_unit.parse()?;
 
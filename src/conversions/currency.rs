@@ -1,127 +1,355 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
 use directories::ProjectDirs;
-use reqwest::blocking::get;
-use serde::{Deserialize, Serialize};
+use once_cell::sync::OnceCell;
 use std::collections::HashMap;
-use std::fs::{self, File};
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-const API_URL: &str = "https://api.neerrrajj.me/currency_rates.json";
+use crate::error::Error;
+use crate::fuzzy;
+use crate::registry::registry;
 
-#[derive(Debug, Deserialize, Serialize)]
-struct ExchangeRates {
-    meta: Meta,
-    data: HashMap<String, CurrencyData>,
+pub mod custom;
+pub mod iso;
+pub mod provider;
+
+pub use custom::Source;
+pub use iso::Currency;
+pub use provider::RateProvider;
+
+/// Rates are read once per process, so a REPL session does not re-read the cache per line
+static RATES: OnceCell<Rates> = OnceCell::new();
+
+static POLICY: OnceCell<RatePolicy> = OnceCell::new();
+
+/// When cached exchange rates are used instead of fetching new ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RatePolicy {
+    /// Only ever use the cache
+    pub offline: bool,
+    /// Age of the rates after which new ones are fetched
+    pub max_age: TimeDelta,
+}
+
+impl Default for RatePolicy {
+    /// Rates are refreshed once they are a day old
+    fn default() -> Self {
+        RatePolicy { offline: false, max_age: TimeDelta::hours(24) }
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-struct Meta {
-    #[serde(rename = "last_updated_at")]
-    last_updated: String,
+/// Sets the policy for loading rates. Only the first call counts, and only before the first
+/// conversion.
+pub fn set_policy(policy: RatePolicy) {
+    let _ = POLICY.set(policy);
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-struct CurrencyData {
-    code: String,
-    value: f64,
+fn policy() -> RatePolicy {
+    POLICY.get().copied().unwrap_or_default()
 }
 
-pub fn convert(value: f64, from: &str, to: &str) -> Result<(f64, String), &'static str> {
-    let rates = get_exchange_rates()?;
+/// Exchange rates on one date, as units of each currency per unit of the base currency
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rates {
+    pub base: String,
+    pub date: DateTime<Utc>,
+    /// Rates by currency code, the base currency's being 1
+    pub rates: HashMap<String, f64>,
+    /// Where the rates of the currencies the user defines come from, see [`custom`]
+    pub custom: HashMap<String, Source>,
+    /// Why cached rates older than the policy allows are used, e.g. `--offline is set`
+    pub stale: Option<String>,
+    /// The day asked for, when there were no rates for it and these are of the closest day
+    pub requested: Option<NaiveDate>,
+}
+
+impl Rates {
+    /// What the caller should tell the user about these rates, if they are stale or of another
+    /// day than the one asked for
+    pub fn warning(&self) -> Option<String> {
+        let day = self.date.format("%Y-%m-%d");
+        match (&self.requested, &self.stale) {
+            (Some(requested), _) => Some(format!("no exchange rates for {}, using those of {}", requested, day)),
+            (None, Some(reason)) => Some(format!("using cached exchange rates from {} ({})", day, reason)),
+            (None, None) => None,
+        }
+    }
+
+    /// Converts between currencies given by their codes, symbols or names, see [`Rates::resolve`]
+    pub fn convert(&self, value: f64, from: &str, to: &str) -> Result<f64, Error> {
+        let from_code = self.resolve(from)?;
+        let to_code = self.resolve(to)?;
+        Ok(value * (self.rates[&to_code] / self.rates[&from_code]))
+    }
+
+    /// The code of a currency with a rate, given by its code in any case, or by a symbol or
+    /// name such as `$`, `€`, `yen` or `euros`, ignoring case and accents
+    pub fn resolve(&self, input: &str) -> Result<String, Error> {
+        let code = input.to_uppercase();
+        if self.rates.contains_key(&code) {
+            return Ok(code);
+        }
 
-    let from_code = resolve_currency_code(from, &rates.data)?;
-    let to_code = resolve_currency_code(to, &rates.data)?;
+        let known: Vec<(&str, Vec<&str>)> = iso::CURRENCIES
+            .iter()
+            .filter(|currency| self.rates.contains_key(currency.code))
+            .map(|currency| (currency.code, currency.names().collect()))
+            .collect();
+        let candidates = || known.iter().map(|(code, names)| (*code, names.as_slice()));
+        // Names are also taken in the plural, as in `100 euros`
+        let singular = input.strip_suffix(['s', 'S']).filter(|singular| !singular.is_empty());
+        if let Some(code) = fuzzy::unique(candidates(), input).or_else(|| singular.and_then(|s| fuzzy::unique(candidates(), s))) {
+            return Ok(code.to_string());
+        }
 
-    let from_rate = rates
-        .data
-        .get(&from_code)
-        .ok_or("Invalid 'from' currency")?
-        .value;
-    let to_rate = rates
-        .data
-        .get(&to_code)
-        .ok_or("Invalid 'to' currency")?
-        .value;
+        let names = self.rates.keys().map(String::as_str).chain(known.iter().flat_map(|(_, names)| names.iter().copied()));
+        let suggestions = fuzzy::suggest(names, input);
+        let elsewhere = registry().find(input).and_then(|unit| registry().dimension(unit.dimension));
+        Err(Error::unknown_unit("currency", input, suggestions).elsewhere(elsewhere))
+    }
 
-    let result = value * (to_rate / from_rate);
-    let formatted_date = format_date(&rates.meta.last_updated)?;
+    /// Codes of every currency with a rate, sorted
+    pub fn codes(&self) -> Vec<String> {
+        let mut codes: Vec<String> = self.rates.keys().cloned().collect();
+        codes.sort();
+        codes
+    }
+}
 
-    Ok(((result * 10000.0).round() / 10000.0, formatted_date))
+/// Converts with the latest rates, giving the date of the rates too
+pub fn convert(value: f64, from: &str, to: &str) -> Result<(f64, DateTime<Utc>), Error> {
+    let rates = latest()?;
+    Ok((rates.convert(value, from, to)?, rates.date))
 }
 
-fn format_date(datetime_str: &str) -> Result<String, &'static str> {
-    let dt = DateTime::parse_from_rfc3339(datetime_str)
-        .map_err(|_| "Invalid date format in response")?;
-    Ok(dt.format("%d %b %H:%M UTC").to_string())
+/// Codes of every currency with a known rate, sorted
+pub fn codes() -> Result<Vec<String>, Error> {
+    Ok(latest()?.codes())
 }
 
-fn resolve_currency_code(
-    input: &str,
-    data: &HashMap<String, CurrencyData>,
-) -> Result<String, &'static str> {
-    if data.contains_key(input.to_uppercase().as_str()) {
-        return Ok(input.to_uppercase());
+/// The currencies with a rate, sorted by code, with their symbols and names and where the rates
+/// of custom ones come from. With a `search`, only those whose code, symbol or names contain it.
+pub fn help_text(search: Option<&str>) -> Result<String, Error> {
+    let rates = latest()?;
+    let folded = search.map(fuzzy::fold);
+    let rows: Vec<(String, &str, String)> = rates
+        .codes()
+        .into_iter()
+        .filter_map(|code| {
+            let currency = iso::find(&code);
+            if let Some(search) = &folded {
+                let mut names = currency.into_iter().flat_map(Currency::names).map(fuzzy::fold).chain([code.to_lowercase()]);
+                if !names.any(|name| name.contains(search.as_str())) {
+                    return None;
+                }
+            }
+            let symbol = currency.map_or("", |currency| currency.symbol);
+            let mut name = currency.map_or_else(String::new, |currency| currency.name.to_string());
+            if let Some(source) = rates.custom.get(&code) {
+                name = format!("{} (custom, from {})", name, source).trim_start().to_string();
+            }
+            let symbol = if symbol == code { "" } else { symbol };
+            Some((code, symbol, name))
+        })
+        .collect();
+
+    if rows.is_empty() {
+        return Ok(format!("No currencies match '{}'\n", search.unwrap_or_default()));
+    }
+    let code_width = rows.iter().map(|(code, _, _)| code.chars().count()).max().unwrap_or(0);
+    let symbol_width = rows.iter().map(|(_, symbol, _)| symbol.chars().count()).max().unwrap_or(0);
+    let mut help = String::from("Supported currencies (Code - Symbol - Name):\n");
+    for (code, symbol, name) in rows {
+        let line = format!("- {:<code_width$}  {:<symbol_width$}  {}", code, symbol, name);
+        help.push_str(line.trim_end());
+        help.push('\n');
     }
+    Ok(help)
+}
 
-    Err("Invalid currency. Use --list to see available options.")
+/// The latest rates, loaded as the policy says, see [`set_policy`]
+pub fn latest() -> Result<&'static Rates, Error> {
+    RATES.get_or_try_init(|| load_exchange_rates().and_then(custom::merge))
 }
 
-pub fn help_text() -> String {
-    match get_exchange_rates() {
-        Ok(rates) => {
-            let mut help = String::from("Supported currencies (Code - Country):\n");
-            for currency in rates.data.values() {
-                help.push_str(&format!("- {}\n", currency.code));
-            }
-            help
-        }
-        Err(e) => format!("Error loading currencies: {}", e),
+/// The rates of a past day: those stored for the day, or else the provider's if it keeps a
+/// history, or else the stored ones of the closest day, which then note the day asked for in
+/// [`Rates::requested`].
+pub fn on(date: NaiveDate) -> Result<Rates, Error> {
+    if date >= Utc::now().date_naive() {
+        return latest().cloned();
+    }
+    let history = history_dir()?;
+    if let Ok(rates) = load_cached_rates(&snapshot_path(&history, date)) {
+        return custom::merge(rates);
     }
+
+    let fetched = if policy().offline { Ok(None) } else { provider::configured()?.on(date) };
+    let rates = match fetched {
+        Ok(Some(rates)) => {
+            save_snapshot(&rates, &history)?;
+            rates
+        }
+        Ok(None) => closest_snapshot(&history, date).ok_or_else(|| {
+            let reason = if policy().offline { "--offline is set" } else { "the rate provider keeps no history" };
+            Error::Cache(format!("no exchange rates are stored for {} or any other day, and {}", date, reason))
+        })?,
+        Err(e) => closest_snapshot(&history, date).ok_or(e)?,
+    };
+
+    let requested = (rates.date.date_naive() != date).then_some(date);
+    custom::merge(Rates { requested, ..rates })
 }
 
-fn get_exchange_rates() -> Result<ExchangeRates, &'static str> {
-    let cache_path = get_cache_path()?;
-    let api_url = API_URL;
+/// Loads the cached rates, or fetches new ones once they are older than the policy allows.
+///
+/// If fetching fails, stale cached rates are used with the reason in [`Rates::stale`], so that
+/// conversions keep working offline. Only without any cache is there an error.
+fn load_exchange_rates() -> Result<Rates, Error> {
+    let policy = policy();
+    let cache_path = cache_dir()?.join("exchange_rates.json");
+    let cached = load_cached_rates(&cache_path).ok();
 
-    if let Ok(cached_rates) = load_cached_rates(&cache_path) {
-        if is_cache_current(&cached_rates) {
-            return Ok(cached_rates);
+    if policy.offline {
+        let rates = cached.ok_or_else(|| {
+            Error::Cache("no exchange rates are cached yet, run once without --offline to fetch them".to_string())
+        })?;
+        if !is_cache_current(&cache_path, policy.max_age) {
+            return Ok(Rates { stale: Some("--offline is set".to_string()), ..rates });
         }
+        return Ok(rates);
+    }
+
+    match cached {
+        Some(rates) if is_cache_current(&cache_path, policy.max_age) => return Ok(rates),
+        _ => {}
     }
 
-    let new_rates = fetch_rates(api_url)?;
-    save_rates(&new_rates, &cache_path)?;
-    Ok(new_rates)
+    match provider::configured()?.latest() {
+        Ok(new_rates) => {
+            save_rates(&new_rates, &cache_path)?;
+            save_snapshot(&new_rates, &history_dir()?)?;
+            Ok(new_rates)
+        }
+        Err(e) => match cached {
+            Some(rates) => Ok(Rates { stale: Some(e.to_string()), ..rates }),
+            None => Err(e),
+        },
+    }
 }
 
-fn load_cached_rates(path: &PathBuf) -> Result<ExchangeRates, &'static str> {
-    let file = File::open(path).map_err(|_| "Failed to open cache")?;
-    serde_json::from_reader(file).map_err(|_| "Invalid cache format")
+/// Reads the cache, which may also be in a format a provider publishes, as older versions wrote
+fn load_cached_rates(path: &Path) -> Result<Rates, Error> {
+    let text = fs::read_to_string(path).map_err(|e| Error::Cache(format!("failed to open cache: {}", e)))?;
+    provider::parse(&text).map_err(|e| Error::Cache(format!("invalid cache format: {}", e)))
 }
 
-fn is_cache_current(rates: &ExchangeRates) -> bool {
-    match DateTime::parse_from_rfc3339(&rates.meta.last_updated) {
-        Ok(dt) => dt.date_naive() == Utc::now().date_naive(),
-        Err(_) => false,
-    }
+/// Whether the cache was fetched within `max_age`, judged by when the file was written rather than
+/// by the date of the rates, which lags behind on days a provider doesn't publish
+fn is_cache_current(path: &Path, max_age: TimeDelta) -> bool {
+    let Ok(modified) = fs::metadata(path).and_then(|metadata| metadata.modified()) else {
+        return false;
+    };
+    Utc::now().signed_duration_since(DateTime::<Utc>::from(modified)) <= max_age
 }
 
-fn fetch_rates(url: &str) -> Result<ExchangeRates, &'static str> {
-    let response = get(url).map_err(|_| "Network error")?;
-    let text = response
-        .text()
-        .map_err(|_| "Failed to read response body")?;
-    serde_json::from_str(&text).map_err(|_| "Invalid API response format")
+/// Writes the rates in the generic `{base, date, rates}` shape
+fn save_rates(rates: &Rates, path: &Path) -> Result<(), Error> {
+    let document = serde_json::json!({
+        "base": rates.base,
+        "date": rates.date.to_rfc3339(),
+        "rates": rates.rates,
+    });
+    fs::write(path, document.to_string()).map_err(|e| Error::Cache(format!("failed to write cache: {}", e)))
 }
 
-fn save_rates(rates: &ExchangeRates, path: &PathBuf) -> Result<(), &'static str> {
-    let file = File::create(path).map_err(|_| "Failed to create cache")?;
-    serde_json::to_writer(file, rates).map_err(|_| "Failed to write cache")
+/// Keeps the rates as the snapshot of their day, one file per day
+fn save_snapshot(rates: &Rates, history: &Path) -> Result<(), Error> {
+    save_rates(rates, &snapshot_path(history, rates.date.date_naive()))
 }
 
-fn get_cache_path() -> Result<PathBuf, &'static str> {
-    let dirs = ProjectDirs::from("", "", "cnv").ok_or("System not supported")?;
+fn snapshot_path(history: &Path, date: NaiveDate) -> PathBuf {
+    history.join(format!("{}.json", date.format("%Y-%m-%d")))
+}
+
+/// The stored snapshot of the day closest to `date`, the earlier one of two as close
+fn closest_snapshot(history: &Path, date: NaiveDate) -> Option<Rates> {
+    let closest = fs::read_dir(history)
+        .ok()?
+        .filter_map(|entry| entry.ok()?.path().file_stem()?.to_str()?.parse::<NaiveDate>().ok())
+        .min_by_key(|day| ((*day - date).num_days().abs(), *day))?;
+    load_cached_rates(&snapshot_path(history, closest)).ok()
+}
+
+/// Where the snapshots of past days are kept
+fn history_dir() -> Result<PathBuf, Error> {
+    let history = cache_dir()?.join("history");
+    fs::create_dir_all(&history).map_err(|e| Error::Cache(format!("can't create cache dir: {}", e)))?;
+    Ok(history)
+}
+
+fn cache_dir() -> Result<PathBuf, Error> {
+    let dirs = ProjectDirs::from("", "", "cnv").ok_or(Error::Cache("system not supported".to_string()))?;
     let cache_dir = dirs.cache_dir();
-    fs::create_dir_all(cache_dir).map_err(|_| "Can't create cache dir")?;
-    Ok(cache_dir.join("exchange_rates.json"))
+    fs::create_dir_all(cache_dir).map_err(|e| Error::Cache(format!("can't create cache dir: {}", e)))?;
+    Ok(cache_dir.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rates() -> Rates {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap().and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let rates = HashMap::from([("EUR".to_string(), 1.0), ("USD".to_string(), 1.08)]);
+        Rates { base: "EUR".to_string(), date, rates, custom: HashMap::new(), stale: None, requested: None }
+    }
+
+    #[test]
+    fn warns_of_stale_and_substituted_rates() {
+        assert_eq!(rates().warning(), None);
+
+        let stale = Rates { stale: Some("--offline is set".to_string()), ..rates() };
+        assert_eq!(stale.warning().as_deref(), Some("using cached exchange rates from 2026-10-16 (--offline is set)"));
+
+        let requested = Rates { requested: NaiveDate::from_ymd_opt(2026, 10, 18), ..rates() };
+        assert_eq!(requested.warning().as_deref(), Some("no exchange rates for 2026-10-18, using those of 2026-10-16"));
+    }
+
+    #[test]
+    fn resolves_codes_symbols_and_names() {
+        let rates = rates();
+        assert_eq!(rates.resolve("usd").unwrap(), "USD");
+        assert_eq!(rates.resolve("euros").unwrap(), "EUR");
+        assert!((rates.convert(10.0, "€", "usd").unwrap() - 10.8).abs() < 1e-12);
+    }
+
+    #[test]
+    fn ages_the_cache_from_when_it_was_fetched() {
+        let path = std::env::temp_dir().join(format!("cnv-cache-{}.json", std::process::id()));
+        save_rates(&rates(), &path).unwrap();
+        assert!(is_cache_current(&path, TimeDelta::hours(1)));
+        assert!(!is_cache_current(&path.with_extension("missing"), TimeDelta::hours(1)));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn keeps_a_snapshot_per_day() {
+        let history = std::env::temp_dir().join(format!("cnv-history-{}", std::process::id()));
+        fs::create_dir_all(&history).unwrap();
+        let friday = rates();
+        let wednesday = Rates { date: friday.date + TimeDelta::days(4), ..rates() };
+        save_snapshot(&friday, &history).unwrap();
+        save_snapshot(&wednesday, &history).unwrap();
+        assert!(snapshot_path(&history, NaiveDate::from_ymd_opt(2026, 10, 16).unwrap()).exists());
+
+        let closest = |day| closest_snapshot(&history, NaiveDate::from_ymd_opt(2026, 10, day).unwrap()).unwrap().date.date_naive();
+        assert_eq!(closest(17), friday.date.date_naive());
+        // Sunday is as close to Friday as to Wednesday, and the earlier day is taken
+        assert_eq!(closest(18), friday.date.date_naive());
+        assert_eq!(closest(19), wednesday.date.date_naive());
+        assert_eq!(closest(25), wednesday.date.date_naive());
+        assert_eq!(closest_snapshot(&history, NaiveDate::from_ymd_opt(2026, 10, 16).unwrap()).unwrap().rates["USD"], 1.08);
+        fs::remove_dir_all(&history).unwrap();
+    }
 }
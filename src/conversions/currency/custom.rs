@@ -0,0 +1,176 @@
+//! Currencies the user defines, such as `BTC`, `XAU` or internal credits, with rates given in
+//! terms of a currency the provider has
+
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use directories::ProjectDirs;
+use once_cell::sync::OnceCell;
+
+use super::Rates;
+use crate::config;
+use crate::error::Error;
+
+/// Definitions are read once per process, so a command giving them is not run per conversion
+static DEFINITIONS: OnceCell<Vec<Definition>> = OnceCell::new();
+
+/// Where the rate of a currency comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// A file of definitions, `CNV_CUSTOM_RATES` or the `currencies` file of the config directory
+    File(PathBuf),
+    /// The output of a command, `CNV_CUSTOM_RATES_COMMAND`
+    Command(String),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Command(command) => write!(f, "`{}`", command),
+        }
+    }
+}
+
+/// `1 CODE = value CURRENCY`, written `CODE = value CURRENCY`
+#[derive(Debug, Clone, PartialEq)]
+struct Definition {
+    code: String,
+    value: f64,
+    currency: String,
+    source: Source,
+}
+
+/// Adds the user's currencies to the rates, which take precedence over the provider's ones of
+/// the same code. The definitions are current ones, so they are used as they are for any date.
+pub(crate) fn merge(rates: Rates) -> Result<Rates, Error> {
+    add(rates, definitions()?)
+}
+
+fn add(mut rates: Rates, definitions: &[Definition]) -> Result<Rates, Error> {
+    for definition in definitions {
+        let Some(rate) = rates.rates.get(&definition.currency) else {
+            return Err(Error::Config(format!(
+                "{} of {} is defined in {}, which has no exchange rate",
+                definition.code, definition.source, definition.currency
+            )));
+        };
+        let rate = rate / definition.value;
+        rates.rates.insert(definition.code.clone(), rate);
+        rates.custom.insert(definition.code.clone(), definition.source.clone());
+    }
+    Ok(rates)
+}
+
+fn definitions() -> Result<&'static Vec<Definition>, Error> {
+    DEFINITIONS.get_or_try_init(|| {
+        let mut definitions = Vec::new();
+        if let Some(path) = file() {
+            let text = fs::read_to_string(&path)
+                .map_err(|e| Error::Config(format!("can't read currencies from {}: {}", path.display(), e)))?;
+            definitions.extend(parse(&text, &Source::File(path))?);
+        }
+        if let Some(command) = config::get("CNV_CUSTOM_RATES_COMMAND") {
+            let text = run(&command)?;
+            definitions.extend(parse(&text, &Source::Command(command))?);
+        }
+        Ok(definitions)
+    })
+}
+
+/// `CNV_CUSTOM_RATES`, or the `currencies` file of the config directory if there is one
+fn file() -> Option<PathBuf> {
+    if let Some(path) = config::get("CNV_CUSTOM_RATES") {
+        return Some(path.into());
+    }
+    let path = ProjectDirs::from("", "", "cnv")?.config_dir().join("currencies");
+    path.exists().then_some(path)
+}
+
+/// Runs a command with the shell, giving what it writes to stdout
+fn run(command: &str) -> Result<String, Error> {
+    let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+    let output = Command::new(shell)
+        .args([flag, command])
+        .output()
+        .map_err(|e| Error::Config(format!("can't run `{}`: {}", command, e)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Config(format!("`{}` failed ({}) {}", command, output.status, stderr.trim())));
+    }
+    String::from_utf8(output.stdout).map_err(|_| Error::Config(format!("`{}` wrote invalid UTF-8", command)))
+}
+
+/// Reads lines such as `BTC = 67000 USD`, with `#` starting comments
+fn parse(text: &str, source: &Source) -> Result<Vec<Definition>, Error> {
+    let invalid = |line: &str| Error::Config(format!("invalid currency '{}' in {}, write e.g. `BTC = 67000 USD`", line, source));
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (code, rate) = line.split_once('=').ok_or_else(|| invalid(line))?;
+            let mut rate = rate.split_whitespace();
+            let (Some(value), Some(currency), None) = (rate.next(), rate.next(), rate.next()) else {
+                return Err(invalid(line));
+            };
+            let value: f64 = value.parse().map_err(|_| invalid(line))?;
+            if !value.is_finite() || value <= 0.0 || code.trim().is_empty() {
+                return Err(invalid(line));
+            }
+            Ok(Definition {
+                code: code.trim().to_uppercase(),
+                value,
+                currency: currency.to_uppercase(),
+                source: source.clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    const CURRENCIES: &str = "
+        # rates of 2026-10-16
+        btc = 67000 USD
+        CREDIT = 0.5 eur  # internal
+        USD = 1.1 EUR
+    ";
+
+    fn rates() -> Rates {
+        let rates = HashMap::from([("EUR".to_string(), 1.0), ("USD".to_string(), 1.08)]);
+        Rates { base: "EUR".to_string(), date: Utc::now(), rates, custom: HashMap::new(), stale: None, requested: None }
+    }
+
+    #[test]
+    fn parses_definitions() {
+        let source = Source::File("currencies".into());
+        let definitions = parse(CURRENCIES, &source).unwrap();
+        let codes: Vec<_> = definitions.iter().map(|d| (d.code.as_str(), d.value, d.currency.as_str())).collect();
+        assert_eq!(codes, [("BTC", 67000.0, "USD"), ("CREDIT", 0.5, "EUR"), ("USD", 1.1, "EUR")]);
+
+        assert!(parse("BTC 67000 USD", &source).is_err());
+        assert!(parse("BTC = -1 USD", &source).is_err());
+        assert!(parse("BTC = 67000 USD EUR", &source).is_err());
+    }
+
+    #[test]
+    fn merges_definitions_over_provider_rates() {
+        let source = Source::File("currencies".into());
+        let merged = add(rates(), &parse(CURRENCIES, &source).unwrap()).unwrap();
+        assert!((merged.rates["BTC"] - 1.08 / 67000.0).abs() < 1e-15);
+        assert_eq!(merged.rates["CREDIT"], 2.0);
+        // A definition takes precedence over the provider's rate of the same code
+        assert!((merged.rates["USD"] - 1.0 / 1.1).abs() < 1e-15);
+        assert_eq!(merged.custom.get("USD"), Some(&source));
+        assert_eq!(merged.custom.get("EUR"), None);
+
+        let unknown = parse("XAU = 2400 CHF", &source).unwrap();
+        assert!(matches!(add(rates(), &unknown), Err(Error::Config(_))));
+    }
+}
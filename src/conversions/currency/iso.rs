@@ -0,0 +1,247 @@
+//! ISO 4217 currencies: their names, symbols and minor units
+
+/// A currency of ISO 4217
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Currency {
+    pub code: &'static str,
+    pub name: &'static str,
+    /// The symbol written in English, e.g. `$` or `CA$`, or the code when there is no other
+    pub symbol: &'static str,
+    /// Digits after the decimal point amounts are written with, e.g. 0 for the yen, or `None`
+    /// for units such as gold that have none
+    pub minor_units: Option<u8>,
+    /// Other symbols and names it is known by, e.g. `¥` and `yen`
+    pub aliases: &'static [&'static str],
+}
+
+impl Currency {
+    /// Every symbol and name the currency is known by, besides its code
+    pub fn names(&self) -> impl Iterator<Item = &'static str> {
+        [self.symbol, self.name].into_iter().chain(self.aliases.iter().copied())
+    }
+
+    /// Writes an amount with the symbol, e.g. `$12.50`, or with the code when the symbol is the
+    /// code, e.g. `12.500 KWD`
+    pub fn amount(&self, number: &str) -> String {
+        if self.symbol == self.code {
+            return format!("{} {}", number, self.code);
+        }
+        match number.strip_prefix('-') {
+            Some(number) => format!("-{}{}", self.symbol, number),
+            None => format!("{}{}", self.symbol, number),
+        }
+    }
+}
+
+/// The currency of a code, in any case
+pub fn find(code: &str) -> Option<&'static Currency> {
+    CURRENCIES.iter().find(|currency| currency.code.eq_ignore_ascii_case(code))
+}
+
+const fn currency(
+    code: &'static str,
+    name: &'static str,
+    symbol: &'static str,
+    minor_units: u8,
+    aliases: &'static [&'static str],
+) -> Currency {
+    Currency { code, name, symbol, minor_units: Some(minor_units), aliases }
+}
+
+const fn without_minor_units(code: &'static str, name: &'static str, aliases: &'static [&'static str]) -> Currency {
+    Currency { code, name, symbol: code, minor_units: None, aliases }
+}
+
+/// Active currencies, with the symbols of the Unicode CLDR for English. Symbols shared by
+/// several currencies, such as `$` or `kr`, are the symbol of one only.
+pub const CURRENCIES: &[Currency] = &[
+    currency("AED", "UAE Dirham", "AED", 2, &["dirham"]),
+    currency("AFN", "Afghan Afghani", "AFN", 2, &["؋", "afghani"]),
+    currency("ALL", "Albanian Lek", "ALL", 2, &["lek"]),
+    currency("AMD", "Armenian Dram", "AMD", 2, &["֏", "dram"]),
+    currency("ANG", "Netherlands Antillean Guilder", "ANG", 2, &[]),
+    currency("AOA", "Angolan Kwanza", "AOA", 2, &["kwanza"]),
+    currency("ARS", "Argentine Peso", "ARS", 2, &[]),
+    currency("AUD", "Australian Dollar", "A$", 2, &["AU$"]),
+    currency("AWG", "Aruban Florin", "AWG", 2, &[]),
+    currency("AZN", "Azerbaijani Manat", "AZN", 2, &["₼"]),
+    currency("BAM", "Bosnia-Herzegovina Convertible Mark", "BAM", 2, &["KM"]),
+    currency("BBD", "Barbadian Dollar", "BBD", 2, &[]),
+    currency("BDT", "Bangladeshi Taka", "BDT", 2, &["৳", "taka"]),
+    currency("BGN", "Bulgarian Lev", "BGN", 2, &["lev"]),
+    currency("BHD", "Bahraini Dinar", "BHD", 3, &[]),
+    currency("BIF", "Burundian Franc", "BIF", 0, &[]),
+    currency("BMD", "Bermudan Dollar", "BMD", 2, &[]),
+    currency("BND", "Brunei Dollar", "BND", 2, &[]),
+    currency("BOB", "Bolivian Boliviano", "BOB", 2, &["boliviano"]),
+    currency("BRL", "Brazilian Real", "R$", 2, &["real", "reais"]),
+    currency("BSD", "Bahamian Dollar", "BSD", 2, &[]),
+    currency("BTN", "Bhutanese Ngultrum", "BTN", 2, &["ngultrum"]),
+    currency("BWP", "Botswanan Pula", "BWP", 2, &["pula"]),
+    currency("BYN", "Belarusian Ruble", "BYN", 2, &[]),
+    currency("BZD", "Belize Dollar", "BZD", 2, &[]),
+    currency("CAD", "Canadian Dollar", "CA$", 2, &["C$"]),
+    currency("CDF", "Congolese Franc", "CDF", 2, &[]),
+    currency("CHF", "Swiss Franc", "CHF", 2, &["franc"]),
+    currency("CLP", "Chilean Peso", "CLP", 0, &[]),
+    currency("CNY", "Chinese Yuan", "CN¥", 2, &["元", "yuan", "renminbi", "rmb"]),
+    currency("COP", "Colombian Peso", "COP", 2, &[]),
+    currency("CRC", "Costa Rican Colón", "CRC", 2, &["₡", "colón"]),
+    currency("CUP", "Cuban Peso", "CUP", 2, &[]),
+    currency("CVE", "Cape Verdean Escudo", "CVE", 2, &["escudo"]),
+    currency("CZK", "Czech Koruna", "CZK", 2, &["Kč", "koruna"]),
+    currency("DJF", "Djiboutian Franc", "DJF", 0, &[]),
+    currency("DKK", "Danish Krone", "DKK", 2, &[]),
+    currency("DOP", "Dominican Peso", "DOP", 2, &[]),
+    currency("DZD", "Algerian Dinar", "DZD", 2, &[]),
+    currency("EGP", "Egyptian Pound", "EGP", 2, &[]),
+    currency("ERN", "Eritrean Nakfa", "ERN", 2, &["nakfa"]),
+    currency("ETB", "Ethiopian Birr", "ETB", 2, &["birr"]),
+    currency("EUR", "Euro", "€", 2, &[]),
+    currency("FJD", "Fijian Dollar", "FJD", 2, &[]),
+    currency("FKP", "Falkland Islands Pound", "FKP", 2, &[]),
+    currency("GBP", "British Pound", "£", 2, &["pound", "pound sterling", "sterling", "quid"]),
+    currency("GEL", "Georgian Lari", "GEL", 2, &["₾", "lari"]),
+    currency("GHS", "Ghanaian Cedi", "GHS", 2, &["₵", "cedi"]),
+    currency("GIP", "Gibraltar Pound", "GIP", 2, &[]),
+    currency("GMD", "Gambian Dalasi", "GMD", 2, &["dalasi"]),
+    currency("GNF", "Guinean Franc", "GNF", 0, &[]),
+    currency("GTQ", "Guatemalan Quetzal", "GTQ", 2, &["quetzal"]),
+    currency("GYD", "Guyanaese Dollar", "GYD", 2, &[]),
+    currency("HKD", "Hong Kong Dollar", "HK$", 2, &[]),
+    currency("HNL", "Honduran Lempira", "HNL", 2, &["lempira"]),
+    currency("HTG", "Haitian Gourde", "HTG", 2, &["gourde"]),
+    currency("HUF", "Hungarian Forint", "HUF", 2, &["Ft", "forint"]),
+    currency("IDR", "Indonesian Rupiah", "IDR", 2, &["Rp", "rupiah"]),
+    currency("ILS", "Israeli New Shekel", "₪", 2, &["shekel", "sheqel"]),
+    currency("INR", "Indian Rupee", "₹", 2, &["rupee"]),
+    currency("IQD", "Iraqi Dinar", "IQD", 3, &[]),
+    currency("IRR", "Iranian Rial", "IRR", 2, &[]),
+    currency("ISK", "Icelandic Króna", "ISK", 0, &[]),
+    currency("JMD", "Jamaican Dollar", "JMD", 2, &[]),
+    currency("JOD", "Jordanian Dinar", "JOD", 3, &[]),
+    currency("JPY", "Japanese Yen", "¥", 0, &["円", "yen"]),
+    currency("KES", "Kenyan Shilling", "KES", 2, &[]),
+    currency("KGS", "Kyrgystani Som", "KGS", 2, &[]),
+    currency("KHR", "Cambodian Riel", "KHR", 2, &["៛", "riel"]),
+    currency("KMF", "Comorian Franc", "KMF", 0, &[]),
+    currency("KPW", "North Korean Won", "KPW", 2, &[]),
+    currency("KRW", "South Korean Won", "₩", 0, &["won"]),
+    currency("KWD", "Kuwaiti Dinar", "KWD", 3, &[]),
+    currency("KYD", "Cayman Islands Dollar", "KYD", 2, &[]),
+    currency("KZT", "Kazakhstani Tenge", "KZT", 2, &["₸", "tenge"]),
+    currency("LAK", "Laotian Kip", "LAK", 2, &["₭", "kip"]),
+    currency("LBP", "Lebanese Pound", "LBP", 2, &[]),
+    currency("LKR", "Sri Lankan Rupee", "LKR", 2, &[]),
+    currency("LRD", "Liberian Dollar", "LRD", 2, &[]),
+    currency("LSL", "Lesotho Loti", "LSL", 2, &["loti"]),
+    currency("LYD", "Libyan Dinar", "LYD", 3, &[]),
+    currency("MAD", "Moroccan Dirham", "MAD", 2, &[]),
+    currency("MDL", "Moldovan Leu", "MDL", 2, &[]),
+    currency("MGA", "Malagasy Ariary", "MGA", 2, &["ariary"]),
+    currency("MKD", "Macedonian Denar", "MKD", 2, &["denar"]),
+    currency("MMK", "Myanmar Kyat", "MMK", 2, &["kyat"]),
+    currency("MNT", "Mongolian Tugrik", "MNT", 2, &["₮", "tugrik"]),
+    currency("MOP", "Macanese Pataca", "MOP", 2, &["pataca"]),
+    currency("MRU", "Mauritanian Ouguiya", "MRU", 2, &["ouguiya"]),
+    currency("MUR", "Mauritian Rupee", "MUR", 2, &[]),
+    currency("MVR", "Maldivian Rufiyaa", "MVR", 2, &["rufiyaa"]),
+    currency("MWK", "Malawian Kwacha", "MWK", 2, &[]),
+    currency("MXN", "Mexican Peso", "MX$", 2, &["peso"]),
+    currency("MYR", "Malaysian Ringgit", "MYR", 2, &["RM", "ringgit"]),
+    currency("MZN", "Mozambican Metical", "MZN", 2, &["metical"]),
+    currency("NAD", "Namibian Dollar", "NAD", 2, &[]),
+    currency("NGN", "Nigerian Naira", "NGN", 2, &["₦", "naira"]),
+    currency("NIO", "Nicaraguan Córdoba", "NIO", 2, &["córdoba"]),
+    currency("NOK", "Norwegian Krone", "NOK", 2, &[]),
+    currency("NPR", "Nepalese Rupee", "NPR", 2, &[]),
+    currency("NZD", "New Zealand Dollar", "NZ$", 2, &[]),
+    currency("OMR", "Omani Rial", "OMR", 3, &[]),
+    currency("PAB", "Panamanian Balboa", "PAB", 2, &["balboa"]),
+    currency("PEN", "Peruvian Sol", "PEN", 2, &["sol"]),
+    currency("PGK", "Papua New Guinean Kina", "PGK", 2, &["kina"]),
+    currency("PHP", "Philippine Peso", "₱", 2, &[]),
+    currency("PKR", "Pakistani Rupee", "PKR", 2, &[]),
+    currency("PLN", "Polish Zloty", "PLN", 2, &["zł", "zloty"]),
+    currency("PYG", "Paraguayan Guarani", "PYG", 0, &["₲", "guarani"]),
+    currency("QAR", "Qatari Riyal", "QAR", 2, &[]),
+    currency("RON", "Romanian Leu", "RON", 2, &["lei"]),
+    currency("RSD", "Serbian Dinar", "RSD", 2, &[]),
+    currency("RUB", "Russian Ruble", "RUB", 2, &["₽", "ruble", "rouble"]),
+    currency("RWF", "Rwandan Franc", "RWF", 0, &[]),
+    currency("SAR", "Saudi Riyal", "SAR", 2, &["riyal"]),
+    currency("SBD", "Solomon Islands Dollar", "SBD", 2, &[]),
+    currency("SCR", "Seychellois Rupee", "SCR", 2, &[]),
+    currency("SDG", "Sudanese Pound", "SDG", 2, &[]),
+    currency("SEK", "Swedish Krona", "SEK", 2, &["krona"]),
+    currency("SGD", "Singapore Dollar", "SGD", 2, &["S$"]),
+    currency("SHP", "St. Helena Pound", "SHP", 2, &[]),
+    currency("SLE", "Sierra Leonean Leone", "SLE", 2, &["leone"]),
+    currency("SOS", "Somali Shilling", "SOS", 2, &[]),
+    currency("SRD", "Surinamese Dollar", "SRD", 2, &[]),
+    currency("SSP", "South Sudanese Pound", "SSP", 2, &[]),
+    currency("STN", "São Tomé & Príncipe Dobra", "STN", 2, &["dobra"]),
+    currency("SVC", "Salvadoran Colón", "SVC", 2, &[]),
+    currency("SYP", "Syrian Pound", "SYP", 2, &[]),
+    currency("SZL", "Swazi Lilangeni", "SZL", 2, &["lilangeni"]),
+    currency("THB", "Thai Baht", "THB", 2, &["฿", "baht"]),
+    currency("TJS", "Tajikistani Somoni", "TJS", 2, &["somoni"]),
+    currency("TMT", "Turkmenistani Manat", "TMT", 2, &[]),
+    currency("TND", "Tunisian Dinar", "TND", 3, &[]),
+    currency("TOP", "Tongan Paʻanga", "TOP", 2, &["paʻanga", "pa'anga"]),
+    currency("TRY", "Turkish Lira", "TRY", 2, &["₺", "lira"]),
+    currency("TTD", "Trinidad & Tobago Dollar", "TTD", 2, &[]),
+    currency("TWD", "New Taiwan Dollar", "NT$", 2, &[]),
+    currency("TZS", "Tanzanian Shilling", "TZS", 2, &[]),
+    currency("UAH", "Ukrainian Hryvnia", "UAH", 2, &["₴", "hryvnia"]),
+    currency("UGX", "Ugandan Shilling", "UGX", 0, &[]),
+    currency("USD", "US Dollar", "$", 2, &["US$", "dollar", "buck"]),
+    currency("UYU", "Uruguayan Peso", "UYU", 2, &[]),
+    currency("UZS", "Uzbekistani Som", "UZS", 2, &[]),
+    currency("VES", "Venezuelan Bolívar", "VES", 2, &["bolívar"]),
+    currency("VND", "Vietnamese Dong", "₫", 0, &["dong"]),
+    currency("VUV", "Vanuatu Vatu", "VUV", 0, &["vatu"]),
+    currency("WST", "Samoan Tala", "WST", 2, &["tala"]),
+    currency("XAF", "Central African CFA Franc", "FCFA", 0, &[]),
+    currency("XCD", "East Caribbean Dollar", "EC$", 2, &[]),
+    currency("XCG", "Caribbean Guilder", "XCG", 2, &[]),
+    currency("XOF", "West African CFA Franc", "F CFA", 0, &[]),
+    currency("XPF", "CFP Franc", "CFPF", 0, &[]),
+    currency("YER", "Yemeni Rial", "YER", 2, &[]),
+    currency("ZAR", "South African Rand", "ZAR", 2, &["R", "rand"]),
+    currency("ZMW", "Zambian Kwacha", "ZMW", 2, &[]),
+    currency("ZWG", "Zimbabwean Gold", "ZWG", 2, &[]),
+    without_minor_units("XAG", "Silver", &["troy ounce of silver"]),
+    without_minor_units("XAU", "Gold", &["troy ounce of gold"]),
+    without_minor_units("XDR", "Special Drawing Rights", &["SDR"]),
+    without_minor_units("XPD", "Palladium", &[]),
+    without_minor_units("XPT", "Platinum", &[]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{format_number, NumberStyle};
+
+    /// An amount rounded to the minor units of its currency and written with its symbol
+    fn written(value: f64, code: &str) -> String {
+        let currency = find(code).unwrap();
+        let style = NumberStyle { precision: currency.minor_units.map(usize::from), ..NumberStyle::default() };
+        currency.amount(&format_number(value, &style))
+    }
+
+    #[test]
+    fn rounds_to_minor_units() {
+        assert_eq!(written(1234.5678, "jpy"), "¥1235");
+        assert_eq!(written(12.34567, "KWD"), "12.346 KWD");
+        assert_eq!(written(-12.5, "usd"), "-$12.50");
+        assert_eq!(find("XAU").unwrap().minor_units, None);
+    }
+
+    #[test]
+    fn finds_codes_in_any_case() {
+        assert_eq!(find("jpy").map(|currency| currency.name), Some("Japanese Yen"));
+        assert!(find("XYZ").is_none());
+        assert!(find("JPY").unwrap().names().any(|name| name == "yen"));
+    }
+}
@@ -0,0 +1,352 @@
+//! Sources of exchange rates, and the formats they publish them in
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::Rates;
+use crate::config;
+use crate::error::Error;
+
+/// The endpoint rates come from unless `CNV_RATES_URL` names another
+const DEFAULT_URL: &str = "https://api.neerrrajj.me/currency_rates.json";
+/// The daily reference rates of the European Central Bank
+const ECB_URL: &str = "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-daily.xml";
+/// Every reference rate of the European Central Bank since 1999
+const ECB_HISTORY_URL: &str = "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-hist.xml";
+
+/// A source of exchange rates
+pub trait RateProvider {
+    /// The latest rates the source has
+    fn latest(&self) -> Result<Rates, Error>;
+
+    /// The rates of a past day, or of the closest day before it the source has rates for, as
+    /// there are none on weekends. `None` if the source keeps no history.
+    fn on(&self, _date: NaiveDate) -> Result<Option<Rates>, Error> {
+        Ok(None)
+    }
+}
+
+/// A JSON document in the schema of currencyapi.com, `{"meta": {"last_updated_at": …},
+/// "data": {"EUR": {"code": "EUR", "value": 0.9}, …}}`. Rates are against the US dollar unless
+/// `meta` names another `base_currency`.
+pub struct CurrencyApi {
+    pub url: String,
+    /// Where the rates of past days are, `{date}` standing for the day, e.g.
+    /// `https://api.currencyapi.com/v3/historical?date={date}&apikey=…`
+    pub history_url: Option<String>,
+}
+
+/// An XML feed in the format of the European Central Bank, with rates against the euro
+pub struct Ecb {
+    pub url: String,
+    /// A feed of the rates of past days, which may hold any number of days
+    pub history_url: Option<String>,
+}
+
+/// A JSON document of the shape `{"base": "EUR", "date": "2024-05-17", "rates": {"USD": 1.08, …}}`,
+/// as served by frankfurter.app and many mirrors
+pub struct GenericJson {
+    pub url: String,
+    /// Where the rates of past days are, `{date}` standing for the day, e.g.
+    /// `https://api.frankfurter.app/{date}`
+    pub history_url: Option<String>,
+}
+
+/// A file on disk in any of the formats above, e.g. a test fixture. Its history is the days the
+/// file holds, which for an ECB feed may be many.
+pub struct LocalFile {
+    pub path: PathBuf,
+}
+
+impl RateProvider for CurrencyApi {
+    fn latest(&self) -> Result<Rates, Error> {
+        parse_currency_api(&download(&self.url)?)
+    }
+
+    fn on(&self, date: NaiveDate) -> Result<Option<Rates>, Error> {
+        match &self.history_url {
+            Some(url) => parse_currency_api(&download(&on_date(url, date))?).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl RateProvider for Ecb {
+    fn latest(&self) -> Result<Rates, Error> {
+        latest(parse_ecb(&download(&self.url)?)?)
+    }
+
+    fn on(&self, date: NaiveDate) -> Result<Option<Rates>, Error> {
+        match &self.history_url {
+            Some(url) => Ok(on_or_before(parse_ecb(&download(&on_date(url, date))?)?, date)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl RateProvider for GenericJson {
+    fn latest(&self) -> Result<Rates, Error> {
+        parse_generic(&download(&self.url)?)
+    }
+
+    fn on(&self, date: NaiveDate) -> Result<Option<Rates>, Error> {
+        match &self.history_url {
+            Some(url) => parse_generic(&download(&on_date(url, date))?).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl RateProvider for LocalFile {
+    fn latest(&self) -> Result<Rates, Error> {
+        latest(parse_days(&self.read()?)?)
+    }
+
+    fn on(&self, date: NaiveDate) -> Result<Option<Rates>, Error> {
+        Ok(on_or_before(parse_days(&self.read()?)?, date))
+    }
+}
+
+impl LocalFile {
+    fn read(&self) -> Result<String, Error> {
+        fs::read_to_string(&self.path)
+            .map_err(|e| Error::Config(format!("can't read rates from {}: {}", self.path.display(), e)))
+    }
+}
+
+/// The provider named by `CNV_RATES_PROVIDER` (`currencyapi`, `ecb`, `json` or `file`), reading
+/// from `CNV_RATES_URL`, which is a path for `file`, and past days from `CNV_RATES_HISTORY_URL`.
+/// All are read from the environment or the config file, see [`config`].
+pub fn configured() -> Result<Box<dyn RateProvider>, Error> {
+    let url = config::get("CNV_RATES_URL");
+    let history_url = config::get("CNV_RATES_HISTORY_URL");
+    let name = config::get("CNV_RATES_PROVIDER").unwrap_or_else(|| "currencyapi".to_string());
+    let provider: Box<dyn RateProvider> = match name.to_lowercase().as_str() {
+        "currencyapi" => Box::new(CurrencyApi { url: url.unwrap_or_else(|| DEFAULT_URL.to_string()), history_url }),
+        "ecb" => Box::new(Ecb {
+            url: url.unwrap_or_else(|| ECB_URL.to_string()),
+            history_url: Some(history_url.unwrap_or_else(|| ECB_HISTORY_URL.to_string())),
+        }),
+        "json" => Box::new(GenericJson { url: url.ok_or_else(|| missing_url("json"))?, history_url }),
+        "file" => Box::new(LocalFile { path: url.ok_or_else(|| missing_url("file"))?.into() }),
+        _ => {
+            return Err(Error::Config(format!(
+                "unknown rate provider '{}' in CNV_RATES_PROVIDER, use currencyapi, ecb, json or file",
+                name
+            )))
+        }
+    };
+    Ok(provider)
+}
+
+fn missing_url(provider: &str) -> Error {
+    Error::Config(format!("the {} rate provider needs CNV_RATES_URL", provider))
+}
+
+/// The URL of a day's rates, with `{date}` replaced by the day
+fn on_date(url: &str, date: NaiveDate) -> String {
+    url.replace("{date}", &date.format("%Y-%m-%d").to_string())
+}
+
+fn download(url: &str) -> Result<String, Error> {
+    let response = reqwest::blocking::get(url)
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| Error::Network(e.to_string()))?;
+    response
+        .text()
+        .map_err(|e| Error::Network(format!("failed to read response body: {}", e)))
+}
+
+/// Reads rates in whichever format they are written: ECB XML, the currencyapi schema or the
+/// generic JSON shape, which is also the one of the cache
+pub(crate) fn parse(text: &str) -> Result<Rates, Error> {
+    latest(parse_days(text)?)
+}
+
+/// Reads the rates of every day a document holds, which is one but for ECB feeds
+fn parse_days(text: &str) -> Result<Vec<Rates>, Error> {
+    if text.trim_start().starts_with('<') {
+        return parse_ecb(text);
+    }
+    let json: Value = serde_json::from_str(text).map_err(|e| Error::InvalidResponse(e.to_string()))?;
+    if json.get("data").is_some() && json.get("meta").is_some() {
+        parse_currency_api(text).map(|rates| vec![rates])
+    } else {
+        parse_generic(text).map(|rates| vec![rates])
+    }
+}
+
+fn latest(days: Vec<Rates>) -> Result<Rates, Error> {
+    days.into_iter()
+        .max_by_key(|rates| rates.date)
+        .ok_or_else(|| Error::InvalidResponse("no rates in the document".to_string()))
+}
+
+fn on_or_before(days: Vec<Rates>, date: NaiveDate) -> Option<Rates> {
+    days.into_iter()
+        .filter(|rates| rates.date.date_naive() <= date)
+        .max_by_key(|rates| rates.date)
+}
+
+fn parse_currency_api(text: &str) -> Result<Rates, Error> {
+    #[derive(Deserialize)]
+    struct Document {
+        meta: Meta,
+        data: HashMap<String, CurrencyData>,
+    }
+    #[derive(Deserialize)]
+    struct Meta {
+        last_updated_at: String,
+        /// Given when the rates were asked for against another currency than the default
+        base_currency: Option<String>,
+    }
+    #[derive(Deserialize)]
+    struct CurrencyData {
+        value: f64,
+    }
+
+    let document: Document = serde_json::from_str(text).map_err(|e| Error::InvalidResponse(e.to_string()))?;
+    let mut rates: HashMap<String, f64> = document.data.into_iter().map(|(code, data)| (code, data.value)).collect();
+    // currencyapi.com gives rates against the US dollar unless asked for another base
+    let base = document.meta.base_currency.map_or_else(|| "USD".to_string(), |base| base.to_uppercase());
+    rates.insert(base.clone(), 1.0);
+    Ok(Rates { base, date: parse_date(&document.meta.last_updated_at)?, rates, custom: HashMap::new(), stale: None, requested: None })
+}
+
+fn parse_generic(text: &str) -> Result<Rates, Error> {
+    #[derive(Deserialize)]
+    struct Document {
+        base: String,
+        date: Option<String>,
+        rates: HashMap<String, f64>,
+    }
+
+    let document: Document = serde_json::from_str(text).map_err(|e| Error::InvalidResponse(e.to_string()))?;
+    // Without its date the rates could not be told apart from today's, however old they are
+    let date = parse_date(&document.date.ok_or_else(|| Error::InvalidResponse("the rates have no date".to_string()))?)?;
+    let mut rates = document.rates;
+    rates.insert(document.base.to_uppercase(), 1.0);
+    Ok(Rates { base: document.base.to_uppercase(), date, rates, custom: HashMap::new(), stale: None, requested: None })
+}
+
+/// Reads `<Cube time="…">` elements and the `<Cube currency="USD" rate="1.08"/>` ones within
+/// them, which is all of an ECB feed that matters. The daily feed holds one day, the historical
+/// one every day.
+fn parse_ecb(text: &str) -> Result<Vec<Rates>, Error> {
+    let mut days: Vec<Rates> = Vec::new();
+    for element in text.split("<Cube").skip(1) {
+        let element = element.split('>').next().unwrap_or_default();
+        if let Some(time) = attribute(element, "time") {
+            let rates = HashMap::from([("EUR".to_string(), 1.0)]);
+            days.push(Rates { base: "EUR".to_string(), date: parse_date(time)?, rates, custom: HashMap::new(), stale: None, requested: None });
+        }
+        if let (Some(currency), Some(rate)) = (attribute(element, "currency"), attribute(element, "rate")) {
+            let rate = rate.parse().map_err(|_| Error::InvalidResponse(format!("invalid rate '{}' for {}", rate, currency)))?;
+            let day = days.last_mut().ok_or_else(|| Error::InvalidResponse("rates without a date in the ECB feed".to_string()))?;
+            day.rates.insert(currency.to_uppercase(), rate);
+        }
+    }
+
+    if days.is_empty() {
+        return Err(Error::InvalidResponse("no rates in the ECB feed".to_string()));
+    }
+    Ok(days)
+}
+
+/// The value of an XML attribute quoted with either `'` or `"`
+fn attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let start = element.find(&format!(" {}=", name))? + name.len() + 2;
+    let quote = element[start..].chars().next()?;
+    let value = &element[start + 1..];
+    Some(&value[..value.find(quote)?])
+}
+
+/// Reads a date with a time, as in `2024-05-17T23:59:59Z`, or a day, taken at midnight UTC
+pub(crate) fn parse_date(date: &str) -> Result<DateTime<Utc>, Error> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(date) {
+        return Ok(date.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|day| day.and_hms_opt(0, 0, 0).expect("midnight exists").and_utc())
+        .map_err(|_| Error::InvalidResponse(format!("invalid date '{}'", date)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_base_of_currencyapi_documents() {
+        let usd = r#"{"meta": {"last_updated_at": "2026-10-16T23:59:59Z"},
+            "data": {"EUR": {"code": "EUR", "value": 0.92}, "JPY": {"code": "JPY", "value": 1.0}}}"#;
+        let rates = parse_currency_api(usd).unwrap();
+        assert_eq!(rates.base, "USD");
+        assert_eq!(rates.rates["USD"], 1.0);
+
+        let eur = r#"{"meta": {"last_updated_at": "2026-10-16T23:59:59Z", "base_currency": "eur"},
+            "data": {"EUR": {"code": "EUR", "value": 1.0}, "USD": {"code": "USD", "value": 1.08}}}"#;
+        let rates = parse_currency_api(eur).unwrap();
+        assert_eq!(rates.base, "EUR");
+        assert_eq!(rates.rates["USD"], 1.08);
+    }
+
+    #[test]
+    fn requires_the_date_of_generic_documents() {
+        let dated = r#"{"base": "eur", "date": "2026-10-16", "rates": {"USD": 1.08}}"#;
+        let rates = parse_generic(dated).unwrap();
+        assert_eq!(rates.base, "EUR");
+        assert_eq!(rates.date.date_naive(), NaiveDate::from_ymd_opt(2026, 10, 16).unwrap());
+
+        let undated = r#"{"base": "EUR", "rates": {"USD": 1.08}}"#;
+        assert!(matches!(parse_generic(undated), Err(Error::InvalidResponse(_))));
+    }
+
+    /// Two days of the ECB's historical feed, Friday 2026-10-16 and Thursday 2026-10-15
+    const ECB: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <gesmes:Envelope xmlns:gesmes="http://www.gesmes.org/xml/2002-08-01" xmlns="http://www.ecb.int/vocabulary/2002-08-01/eurofxref">
+            <gesmes:subject>Reference rates</gesmes:subject>
+            <Cube>
+                <Cube time="2026-10-16">
+                    <Cube currency="USD" rate="1.0812"/>
+                    <Cube currency='JPY' rate='161.25'/>
+                </Cube>
+                <Cube time="2026-10-15">
+                    <Cube currency="USD" rate="1.0790"/>
+                </Cube>
+            </Cube>
+        </gesmes:Envelope>"#;
+
+    fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn reads_every_day_of_ecb_feeds() {
+        let days = parse_ecb(ECB).unwrap();
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].date.date_naive(), day(2026, 10, 16));
+        assert_eq!(days[0].base, "EUR");
+        assert_eq!(days[0].rates["EUR"], 1.0);
+        assert_eq!(days[0].rates["JPY"], 161.25);
+        assert_eq!(days[1].rates["USD"], 1.079);
+        assert!(!days[1].rates.contains_key("JPY"));
+
+        assert_eq!(parse(ECB).unwrap().date.date_naive(), day(2026, 10, 16));
+        assert!(matches!(parse_ecb("<Cube></Cube>"), Err(Error::InvalidResponse(_))));
+        assert!(matches!(parse_ecb(r#"<Cube time="2026-10-16"><Cube currency="USD" rate="x"/>"#), Err(Error::InvalidResponse(_))));
+    }
+
+    #[test]
+    fn takes_the_last_day_before_days_without_rates() {
+        let sunday = on_or_before(parse_ecb(ECB).unwrap(), day(2026, 10, 18)).unwrap();
+        assert_eq!(sunday.date.date_naive(), day(2026, 10, 16));
+        let thursday = on_or_before(parse_ecb(ECB).unwrap(), day(2026, 10, 15)).unwrap();
+        assert_eq!(thursday.rates["USD"], 1.079);
+        assert!(on_or_before(parse_ecb(ECB).unwrap(), day(2026, 10, 14)).is_none());
+    }
+}
@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use super::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DataStorageUnit {
     Bit,
     Byte,
@@ -40,6 +40,8 @@ pub enum DataStorageUnit {
     Yobibyte,
 }
 
+pub struct DataStorage;
+
 pub struct UnitDef {
     variant: DataStorageUnit,
     name: &'static str,
@@ -83,14 +85,14 @@ const UNIT_DEFS: &[UnitDef] = &[
     UnitDef { variant: DataStorageUnit::Yobibyte, name: "Yobibyte", aliases: &["YiB", "yobibyte"] },
 ];
 
-impl_conversion_traits!(DataStorageUnit, UNIT_DEFS);
+impl_conversion_traits!(DataStorage, DataStorageUnit, UNIT_DEFS, "data storage", "ds", BaseDimensions::DATA);
 
 pub fn help_text() -> String {
     DataStorageUnit::generate_help_text()
 }
 
-impl DataStorageUnit {
-    fn factor(&self) -> f64 {
+impl Scaled for DataStorageUnit {
+    fn factor(self) -> f64 {
         use DataStorageUnit::*;
 
         match self {
@@ -130,12 +132,34 @@ impl DataStorageUnit {
             Yobibyte => 9_671_406_556_917_033_397_649_408.0,
         }
     }
+
+    fn systems(self) -> &'static [System] {
+        use DataStorageUnit::*;
+
+        match self {
+            Bit => &[System::DecimalBits, System::BinaryBits],
+            Byte => &[System::DecimalBytes, System::BinaryBytes],
+            Kilobit | Megabit | Gigabit | Terabit | Petabit | Exabit | Zettabit | Yottabit => &[System::DecimalBits],
+            Kilobyte | Megabyte | Gigabyte | Terabyte | Petabyte | Exabyte | Zettabyte | Yottabyte => {
+                &[System::DecimalBytes]
+            }
+            Kibibit | Mebibit | Gibibit | Tebibit | Pebibit | Exbibit | Zebibit | Yobibit => &[System::BinaryBits],
+            Kibibyte | Mebibyte | Gibibyte | Tebibyte | Pebibyte | Exbibyte | Zebibyte | Yobibyte => &[System::BinaryBytes],
+        }
+    }
+
+    fn prefixes(self) -> Prefixes {
+        use DataStorageUnit::*;
+        match self {
+            Bit | Byte => Prefixes::Data,
+            _ => Prefixes::None,
+        }
+    }
 }
 
-pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, &'static str> {
+pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, Error> {
     let from: DataStorageUnit = from_unit.parse()?;
     let to: DataStorageUnit = to_unit.parse()?;
 
-    let result = value * from.factor() / to.factor();
-    Ok((result * 10000.0).round()/10000.0)
+    Ok(value * from.factor() / to.factor())
 }
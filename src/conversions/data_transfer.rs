@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use super::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DataTransferUnit {
     BitPerSecond,
     BytePerSecond,
@@ -40,6 +40,8 @@ pub enum DataTransferUnit {
     YobibytePerSecond,
 }
 
+pub struct DataTransfer;
+
 pub struct UnitDef {
     variant: DataTransferUnit,
     name: &'static str,
@@ -83,14 +85,14 @@ const UNIT_DEFS: &[UnitDef] = &[
     UnitDef { variant: DataTransferUnit::YobibytePerSecond, name: "Yobibyte/s", aliases: &["YiBps", "YiB/s"] },
 ];
 
-impl_conversion_traits!(DataTransferUnit, UNIT_DEFS);
+impl_conversion_traits!(DataTransfer, DataTransferUnit, UNIT_DEFS, "data transfer", "dt", BaseDimensions::DATA_RATE);
 
 pub fn help_text() -> String {
     DataTransferUnit::generate_help_text()
 }
 
-impl DataTransferUnit {
-    fn factor(&self) -> f64 {
+impl Scaled for DataTransferUnit {
+    fn factor(self) -> f64 {
         use DataTransferUnit::*;
 
         match self {
@@ -130,12 +132,36 @@ impl DataTransferUnit {
             YobibytePerSecond => 1_208_925_819_614_629_174_706_176.0 * 8.0,
         }
     }
+
+    fn systems(self) -> &'static [System] {
+        use DataTransferUnit::*;
+
+        match self {
+            BitPerSecond => &[System::DecimalBits, System::BinaryBits],
+            BytePerSecond => &[System::DecimalBytes, System::BinaryBytes],
+            KilobitPerSecond | MegabitPerSecond | GigabitPerSecond | TerabitPerSecond | PetabitPerSecond
+            | ExabitPerSecond | ZettabitPerSecond | YottabitPerSecond => &[System::DecimalBits],
+            KilobytePerSecond | MegabytePerSecond | GigabytePerSecond | TerabytePerSecond | PetabytePerSecond
+            | ExabytePerSecond | ZettabytePerSecond | YottabytePerSecond => &[System::DecimalBytes],
+            KibibitPerSecond | MebibitPerSecond | GibibitPerSecond | TebibitPerSecond | PebibitPerSecond
+            | ExbibitPerSecond | ZebibitPerSecond | YobibitPerSecond => &[System::BinaryBits],
+            KibibytePerSecond | MebibytePerSecond | GibibytePerSecond | TebibytePerSecond | PebibytePerSecond
+            | ExbibytePerSecond | ZebibytePerSecond | YobibytePerSecond => &[System::BinaryBytes],
+        }
+    }
+
+    fn prefixes(self) -> Prefixes {
+        use DataTransferUnit::*;
+        match self {
+            BitPerSecond | BytePerSecond => Prefixes::Data,
+            _ => Prefixes::None,
+        }
+    }
 }
 
-pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, &'static str> {
+pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, Error> {
     let from: DataTransferUnit = from_unit.parse()?;
     let to: DataTransferUnit = to_unit.parse()?;
 
-    let result = value * from.factor() / to.factor();
-    Ok((result * 10000.0).round() / 10000.0)
+    Ok(value * from.factor() / to.factor())
 }
@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use super::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DistanceUnit {
     Nanometer,
     Micrometer,
@@ -37,6 +37,8 @@ pub enum DistanceUnit {
     Angstorm,
 }
 
+pub struct Distance;
+
 pub struct UnitDef {
     variant: DistanceUnit,
     name: &'static str,
@@ -201,14 +203,14 @@ const UNIT_DEFS: &[UnitDef] = &[
     },
 ];
 
-impl_conversion_traits!(DistanceUnit, UNIT_DEFS);
+impl_conversion_traits!(Distance, DistanceUnit, UNIT_DEFS, "distance", "dist", BaseDimensions::LENGTH);
 
 pub fn help_text() -> String {
     DistanceUnit::generate_help_text()
 }
 
-impl DistanceUnit {
-    fn factor(&self) -> f64 {
+impl Scaled for DistanceUnit {
+    fn factor(self) -> f64 {
         use DistanceUnit::*;
 
         match self {
@@ -245,12 +247,39 @@ impl DistanceUnit {
             Parsec => 3.0857e16,
         }
     }
+
+    fn systems(self) -> &'static [System] {
+        use DistanceUnit::*;
+
+        match self {
+            Nanometer | Micrometer | Millimeter | Centimeter | Meter | Kilometer => &[System::Metric],
+            Inch | Foot | Yard | Mile => &[System::Imperial],
+            _ => &[],
+        }
+    }
+
+    fn exact_factor(self) -> Option<Exact> {
+        use DistanceUnit::*;
+        match self {
+            // The Julian light-year is defined exactly, the table rounds it
+            LightYear => Some(Exact::fraction(9460730472580800, 1)),
+            Parsec => None,
+            unit => Exact::decimal(unit.factor()),
+        }
+    }
+
+    fn prefixes(self) -> Prefixes {
+        use DistanceUnit::*;
+        match self {
+            Meter => Prefixes::Si,
+            _ => Prefixes::None,
+        }
+    }
 }
 
-pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, &'static str> {
+pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, Error> {
     let from: DistanceUnit = from_unit.parse()?;
     let to: DistanceUnit = to_unit.parse()?;
 
-    let result = value * from.factor() / to.factor();
-    Ok((result * 10000.0).round()/10000.0)
+    Ok(value * from.factor() / to.factor())
 }
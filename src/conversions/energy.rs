@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use super::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum EnergyUnit {
     Joule,
     Kilojoule,
@@ -37,6 +37,8 @@ pub enum EnergyUnit {
     HorsepowerHour,
 }
 
+pub struct Energy;
+
 pub struct UnitDef {
     variant: EnergyUnit,
     name: &'static str,
@@ -77,14 +79,14 @@ const UNIT_DEFS: &[UnitDef] = &[
     UnitDef { variant: EnergyUnit::HorsepowerHour, name: "Horsepower Hour", aliases: &["hp-h", "horsepower hour", "horsepower hours", "horsepower-hour", "horsepower-hours"] },
 ];
 
-impl_conversion_traits!(EnergyUnit, UNIT_DEFS);
+impl_conversion_traits!(Energy, EnergyUnit, UNIT_DEFS, "energy", "energy", BaseDimensions::ENERGY);
 
 pub fn help_text() -> String {
     EnergyUnit::generate_help_text()
 }
 
-impl EnergyUnit {
-    fn factor(&self) -> f64 {
+impl Scaled for EnergyUnit {
+    fn factor(self) -> f64 {
         use EnergyUnit::*;
 
         match self {
@@ -111,7 +113,7 @@ impl EnergyUnit {
             MegawattHour => 3.6e9,
             GigawattHour => 3.6e12,
             TerawattHour => 3.6e15,
-            BritishThermalUnit => 1055.06,
+            BritishThermalUnit => 1055.05585262,
             Therm => 1.05506e8,
             FootPound => 1.35582,
             InchPound => 0.112985,
@@ -121,12 +123,38 @@ impl EnergyUnit {
             HorsepowerHour => 2.68452e6,
         }
     }
+
+    fn exact_factor(self) -> Option<Exact> {
+        use EnergyUnit::*;
+        let foot_pound = Exact::decimal(0.3048)?.times(Exact::decimal(4.4482216152605)?);
+        match self {
+            // The table rounds these, their definitions are exact
+            Electronvolt => Exact::decimal(1.602176634e-19),
+            Kiloelectronvolt => Exact::decimal(1.602176634e-16),
+            Megaelectronvolt => Exact::decimal(1.602176634e-13),
+            Gigaelectronvolt => Exact::decimal(1.602176634e-10),
+            Teraelectronvolt => Exact::decimal(1.602176634e-7),
+            BritishThermalUnit => Exact::decimal(1055.05585262),
+            Therm => Exact::decimal(105505585.262),
+            FootPound => Some(foot_pound),
+            InchPound => Some(foot_pound.over(12)),
+            HorsepowerHour => Some(foot_pound.times(Exact::fraction(550 * 3600, 1))),
+            unit => Exact::decimal(unit.factor()),
+        }
+    }
+
+    fn prefixes(self) -> Prefixes {
+        use EnergyUnit::*;
+        match self {
+            Joule | Electronvolt | WattHour => Prefixes::Si,
+            _ => Prefixes::None,
+        }
+    }
 }
 
-pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, &'static str> {
+pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, Error> {
     let from: EnergyUnit = from_unit.parse()?;
     let to: EnergyUnit = to_unit.parse()?;
 
-    let result = value * from.factor() / to.factor();
-    Ok((result * 10000.0).round() / 10000.0)
+    Ok(value * from.factor() / to.factor())
 }
@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use super::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ForceUnit {
     Yoctonewton,
     Zeptonewton,
@@ -34,6 +34,8 @@ pub enum ForceUnit {
     LongTonForce,
 }
 
+pub struct Force;
+
 pub struct UnitDef {
     variant: ForceUnit,
     name: &'static str,
@@ -71,14 +73,14 @@ const UNIT_DEFS: &[UnitDef] = &[
     UnitDef { variant: ForceUnit::LongTonForce, name: "LongTonForce", aliases: &["long ton-force", "uk ton-force", "long tons-force"] },
 ];
 
-impl_conversion_traits!(ForceUnit, UNIT_DEFS);
+impl_conversion_traits!(Force, ForceUnit, UNIT_DEFS, "force", "force", BaseDimensions::FORCE);
 
 pub fn help_text() -> String {
     ForceUnit::generate_help_text()
 }
 
-impl ForceUnit {
-    fn factor(&self) -> f64 {
+impl Scaled for ForceUnit {
+    fn factor(self) -> f64 {
         use ForceUnit::*;
         
         match self {
@@ -112,12 +114,27 @@ impl ForceUnit {
             LongTonForce => 9964.01641818352,
         }
     }
+
+    fn exact_factor(self) -> Option<Exact> {
+        use ForceUnit::*;
+        match self {
+            OunceForce => Some(Exact::decimal(4.4482216152605)?.over(16)),
+            unit => Exact::decimal(unit.factor()),
+        }
+    }
+
+    fn prefixes(self) -> Prefixes {
+        use ForceUnit::*;
+        match self {
+            Newton => Prefixes::Si,
+            _ => Prefixes::None,
+        }
+    }
 }
 
-pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, &'static str> {
+pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, Error> {
     let from: ForceUnit = from_unit.parse()?;
     let to: ForceUnit = to_unit.parse()?;
 
-    let result = value * from.factor() / to.factor();
-    Ok((result * 10000.0).round() / 10000.0)
+    Ok(value * from.factor() / to.factor())
 }
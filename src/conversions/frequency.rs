@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use super::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FrequencyUnit {
     Yoctohertz,
     Zeptohertz,
@@ -34,6 +34,8 @@ pub enum FrequencyUnit {
     Savart,
 }
 
+pub struct Frequency;
+
 pub struct UnitDef {
     variant: FrequencyUnit,
     name: &'static str,
@@ -71,14 +73,14 @@ const UNIT_DEFS: &[UnitDef] = &[
     UnitDef { variant: FrequencyUnit::Savart, name: "Savart", aliases: &["savart", "Savarts", "savarts"] },
 ];
 
-impl_conversion_traits!(FrequencyUnit, UNIT_DEFS);
+impl_conversion_traits!(Frequency, FrequencyUnit, UNIT_DEFS, "frequency", "freq", BaseDimensions::FREQUENCY);
 
 pub fn help_text() -> String {
     FrequencyUnit::generate_help_text()
 }
 
-impl FrequencyUnit {
-    fn factor(&self) -> f64 {
+impl Scaled for FrequencyUnit {
+    fn factor(self) -> f64 {
         use FrequencyUnit::*;
         
         match self {
@@ -112,12 +114,41 @@ impl FrequencyUnit {
             Savart => 1.0 / 300.0,
         }
     }
+
+    fn systems(self) -> &'static [System] {
+        use FrequencyUnit::*;
+
+        match self {
+            Yoctohertz | Zeptohertz | Attohertz | Femtohertz | Picohertz | Nanohertz | Microhertz | Millihertz
+            | Hertz | Kilohertz | Megahertz | Gigahertz | Terahertz | Petahertz | Exahertz | Zettahertz
+            | Yottahertz => &[System::Metric],
+            _ => &[],
+        }
+    }
+
+    fn exact_factor(self) -> Option<Exact> {
+        use FrequencyUnit::*;
+        match self {
+            RevolutionsPerMinute | CyclesPerMinute | BeatsPerMinute => Some(Exact::fraction(1, 60)),
+            RevolutionsPerHour | CyclesPerHour => Some(Exact::fraction(1, 3600)),
+            CyclesPerDay => Some(Exact::fraction(1, 86400)),
+            Savart => Some(Exact::fraction(1, 300)),
+            unit => Exact::decimal(unit.factor()),
+        }
+    }
+
+    fn prefixes(self) -> Prefixes {
+        use FrequencyUnit::*;
+        match self {
+            Hertz => Prefixes::Si,
+            _ => Prefixes::None,
+        }
+    }
 }
 
-pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, &'static str> {
+pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, Error> {
     let from: FrequencyUnit = from_unit.parse()?;
     let to: FrequencyUnit = to_unit.parse()?;
 
-    let result = value * from.factor() / to.factor();
-    Ok((result * 10000.0).round() / 10000.0)
+    Ok(value * from.factor() / to.factor())
 }
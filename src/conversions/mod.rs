@@ -13,21 +13,65 @@ pub mod power;
 pub mod speed;
 pub mod currency;
 
+pub use crate::compound::BaseDimensions;
+pub use crate::error::Error;
+pub(crate) use crate::exact::Exact;
+pub(crate) use crate::prefix::Prefixes;
+pub use crate::registry::{Dimension, System, Unit};
+
 pub trait Help {
     fn generate_help_text() -> String;
 }
 
+/// Placement of a unit on its category's base scale
+pub(crate) trait Scaled: Copy {
+    /// Size of one unit in the base unit
+    fn factor(self) -> f64;
+    /// Reading of this unit at the base unit's zero, for offset scales like Fahrenheit
+    fn zero(self) -> f64 {
+        0.0
+    }
+    /// Systems the unit belongs to, for picking a readable unit; none for units like the parsec
+    fn systems(self) -> &'static [System] {
+        &[]
+    }
+    /// The factor as an exact fraction, for `--exact`. Factors are taken to be exact as written,
+    /// so units with a factor that is a quotient or a measured value have to say otherwise.
+    fn exact_factor(self) -> Option<Exact> {
+        Exact::decimal(self.factor())
+    }
+    fn exact_zero(self) -> Option<Exact> {
+        Exact::decimal(self.zero())
+    }
+    /// Whether the unit measures differences, as `Δc` does, rather than readings on a scale with
+    /// an offset zero
+    fn is_difference(self) -> bool {
+        false
+    }
+    /// Prefixes the unit takes, such as the SI ones for the metre that give `pm` and `Pm`
+    fn prefixes(self) -> Prefixes {
+        Prefixes::None
+    }
+}
+
 macro_rules! impl_conversion_traits {
-    ($type:ty, $unit_defs:ident) => {
+    ($dimension:ident, $type:ty, $unit_defs:ident, $category:expr, $command:expr, $base:expr) => {
+        impl_conversion_traits!($dimension, $type, $unit_defs, $category, $command, $base, 1.0);
+    };
+    ($dimension:ident, $type:ty, $unit_defs:ident, $category:expr, $command:expr, $base:expr, $si_scale:expr) => {
         impl FromStr for $type {
-            type Err = &'static str;
+            type Err = Error;
 
             fn from_str(s: &str) -> Result<Self, Self::Err> {
                 $unit_defs
                     .iter()
                     .find(|def| def.aliases.contains(&s))
                     .map(|def| def.variant)
-                    .ok_or("Invalid unit.\nUse the --list (or) -L flag on a command to see the list of supported units\nUsage: cnv <COMMAND> --list")
+                    .or_else(|| crate::fuzzy::unique($unit_defs.iter().map(|def| (def.variant, def.aliases)), s))
+                    .ok_or_else(|| {
+                        let aliases = $unit_defs.iter().flat_map(|def| def.aliases.iter().copied());
+                        Error::unknown_unit($category, s, crate::fuzzy::suggest(aliases, s))
+                    })
             }
         }
 
@@ -37,14 +81,53 @@ macro_rules! impl_conversion_traits {
                                              List of supported units\n\
                                              ----------------------------");
                 for def in $unit_defs {
-                    help.push_str(&format!("\n* {} : {}", 
+                    help.push_str(&format!("\n* {} : {}{}", 
                         def.name, 
-                        def.aliases.join(", ")
+                        def.aliases.join(", "),
+                        def.variant.prefixes().note()
                     ));
                 }
                 help
             }
         }
+
+        impl Dimension for $dimension {
+            fn name(&self) -> &'static str {
+                $category
+            }
+
+            fn command(&self) -> &'static str {
+                $command
+            }
+
+            fn base_dimensions(&self) -> BaseDimensions {
+                $base
+            }
+
+            fn units(&self) -> Vec<Unit> {
+                $unit_defs
+                    .iter()
+                    .map(|def| Unit {
+                        name: def.name,
+                        aliases: def.aliases,
+                        dimension: $category,
+                        factor: def.variant.factor(),
+                        zero: def.variant.zero(),
+                        si_scale: $si_scale,
+                        base: $base,
+                        systems: def.variant.systems(),
+                        exact_factor: def.variant.exact_factor(),
+                        exact_zero: def.variant.exact_zero(),
+                        difference: def.variant.is_difference(),
+                        prefixes: def.variant.prefixes(),
+                    })
+                    .collect()
+            }
+
+            fn help_text(&self) -> String {
+                <$type>::generate_help_text()
+            }
+        }
     };
 }
 
@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use super::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PowerUnit {
     Watt,
     Deciwatt,
@@ -43,6 +43,8 @@ pub enum PowerUnit {
     Donkeypower, 
 }
 
+pub struct Power;
+
 pub struct UnitDef {
     variant: PowerUnit,
     name: &'static str,
@@ -89,14 +91,14 @@ const UNIT_DEFS: &[UnitDef] = &[
     UnitDef { variant: PowerUnit::Donkeypower, name: "Donkeypower", aliases: &["donkeypower", "donkeypowers", "dp"] },
 ];
 
-impl_conversion_traits!(PowerUnit, UNIT_DEFS);
+impl_conversion_traits!(Power, PowerUnit, UNIT_DEFS, "power", "power", BaseDimensions::POWER);
 
 pub fn help_text() -> String {
     PowerUnit::generate_help_text()
 }
 
-impl PowerUnit {
-    fn factor(&self) -> f64 {
+impl Scaled for PowerUnit {
+    fn factor(self) -> f64 {
         use PowerUnit::*;
 
         match self {
@@ -139,12 +141,36 @@ impl PowerUnit {
             Donkeypower => 250.0,
         }
     }
+
+    fn exact_factor(self) -> Option<Exact> {
+        use PowerUnit::*;
+        let foot_pound = Exact::decimal(0.3048)?.times(Exact::decimal(4.4482216152605)?);
+        let btu = Exact::decimal(1055.05585262)?;
+        match self {
+            CaloriePerHour => Some(Exact::decimal(4.184)?.over(3600)),
+            Horsepower => Some(foot_pound.times(Exact::fraction(550, 1))),
+            FootPoundForcePerMinute => Some(foot_pound.over(60)),
+            BTUPerHour => Some(btu.over(3600)),
+            MBTUPerHour => Some(btu.times(Exact::fraction(1000, 3600))),
+            TonOfRefrigeration => Some(btu.times(Exact::fraction(12000, 3600))),
+            // Rounded from measured values
+            BoilerHorsepower | UKTonOfRefrigeration | Lusec | Clusec => None,
+            unit => Exact::decimal(unit.factor()),
+        }
+    }
+
+    fn prefixes(self) -> Prefixes {
+        use PowerUnit::*;
+        match self {
+            Watt => Prefixes::Si,
+            _ => Prefixes::None,
+        }
+    }
 }
 
-pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, &'static str> {
+pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, Error> {
     let from: PowerUnit = from_unit.parse()?;
     let to: PowerUnit = to_unit.parse()?;
 
-    let result = value * from.factor() / to.factor();
-    Ok((result * 10000.0).round() / 10000.0)
+    Ok(value * from.factor() / to.factor())
 }
@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use super::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SpeedUnit {
     YoctometerPerSecond,
     ZeptometerPerSecond,
@@ -67,6 +67,8 @@ pub enum SpeedUnit {
     FractionOfLightSpeed,
 }
 
+pub struct Speed;
+
 pub struct UnitDef {
     variant: SpeedUnit,
     name: &'static str,
@@ -137,14 +139,14 @@ const UNIT_DEFS: &[UnitDef] = &[
     UnitDef { variant: SpeedUnit::FractionOfLightSpeed, name: "FractionOfLightSpeed", aliases: &["c", "fraction of c", "lightspeed fraction", "light speed fraction"] },
 ];
 
-impl_conversion_traits!(SpeedUnit, UNIT_DEFS);
+impl_conversion_traits!(Speed, SpeedUnit, UNIT_DEFS, "speed", "speed", BaseDimensions::SPEED);
 
 pub fn help_text() -> String {
     SpeedUnit::generate_help_text()
 }
 
-impl SpeedUnit {
-    fn factor(&self) -> f64 {
+impl Scaled for SpeedUnit {
+    fn factor(self) -> f64 {
         use SpeedUnit::*;
         
         match self {
@@ -211,11 +213,52 @@ impl SpeedUnit {
             FractionOfLightSpeed => 299792458.0,
         }
     }
+
+    fn exact_factor(self) -> Option<Exact> {
+        use SpeedUnit::*;
+        match self {
+            PicometerPerMinute => Some(Exact::decimal(1e-12)?.over(60)),
+            NanometerPerMinute => Some(Exact::decimal(1e-9)?.over(60)),
+            MicrometerPerMinute => Some(Exact::decimal(1e-6)?.over(60)),
+            MillimeterPerMinute => Some(Exact::decimal(1e-3)?.over(60)),
+            CentimeterPerMinute => Some(Exact::decimal(1e-2)?.over(60)),
+            MeterPerMinute => Some(Exact::fraction(1, 60)),
+            KilometerPerMinute => Some(Exact::decimal(1e3)?.over(60)),
+            MegameterPerMinute => Some(Exact::decimal(1e6)?.over(60)),
+            GigameterPerMinute => Some(Exact::decimal(1e9)?.over(60)),
+            TerameterPerMinute => Some(Exact::decimal(1e12)?.over(60)),
+            PicometerPerHour => Some(Exact::decimal(1e-12)?.over(3600)),
+            NanometerPerHour => Some(Exact::decimal(1e-9)?.over(3600)),
+            MicrometerPerHour => Some(Exact::decimal(1e-6)?.over(3600)),
+            MillimeterPerHour => Some(Exact::decimal(1e-3)?.over(3600)),
+            CentimeterPerHour => Some(Exact::decimal(1e-2)?.over(3600)),
+            MeterPerHour => Some(Exact::fraction(1, 3600)),
+            KilometerPerHour => Some(Exact::decimal(1e3)?.over(3600)),
+            MegameterPerHour => Some(Exact::decimal(1e6)?.over(3600)),
+            GigameterPerHour => Some(Exact::decimal(1e9)?.over(3600)),
+            TerameterPerHour => Some(Exact::decimal(1e12)?.over(3600)),
+            MilePerMinute => Some(Exact::decimal(1609.344)?.over(60)),
+            MilePerHour => Some(Exact::decimal(1609.344)?.over(3600)),
+            FootPerMinute => Some(Exact::decimal(0.3048)?.over(60)),
+            FootPerHour => Some(Exact::decimal(0.3048)?.over(3600)),
+            InchPerMinute => Some(Exact::decimal(0.0254)?.over(60)),
+            InchPerHour => Some(Exact::decimal(0.0254)?.over(3600)),
+            YardPerMinute => Some(Exact::decimal(0.9144)?.over(60)),
+            YardPerHour => Some(Exact::decimal(0.9144)?.over(3600)),
+            FurlongPerFortnight => Some(Exact::decimal(201.168)?.over(1209600)),
+            LeaguePerHour => Some(Exact::decimal(4828.032)?.over(3600)),
+            Knot => Some(Exact::fraction(1852, 3600)),
+            AdmiraltyKnot => Some(Exact::decimal(1853.184)?.over(3600)),
+            AstronomicalUnitPerDay => Some(Exact::fraction(149597870700, 86400)),
+            // Measured, or depending on the parsec
+            Mach | CosmicVelocityFirst | CosmicVelocitySecond | CosmicVelocityThird | ParsecPerYear => None,
+            unit => Exact::decimal(unit.factor()),
+        }
+    }
 }
-pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, &'static str> {
+pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, Error> {
     let from: SpeedUnit = from_unit.parse()?;
     let to: SpeedUnit = to_unit.parse()?;
 
-    let result = value * from.factor() / to.factor();
-    Ok((result * 10000.0).round() / 10000.0)
+    Ok(value * from.factor() / to.factor())
 }
@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use super::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TemperatureUnit {
     Celsius,
     Fahrenheit,
@@ -12,8 +12,18 @@ pub enum TemperatureUnit {
     Newton,
     Reaumur,
     Romer,
+    DeltaCelsius,
+    DeltaFahrenheit,
+    DeltaKelvin,
+    DeltaRankine,
+    DeltaDelisle,
+    DeltaNewton,
+    DeltaReaumur,
+    DeltaRomer,
 }
 
+pub struct Temperature;
+
 pub struct UnitDef {
     variant: TemperatureUnit,
     name: &'static str,
@@ -34,7 +44,7 @@ const UNIT_DEFS: &[UnitDef] = &[
     UnitDef {
         variant: TemperatureUnit::Kelvin,
         name: "Kelvin",
-        aliases: &["k", "kelvin"],
+        aliases: &["k", "K", "kelvin"],
     },
     UnitDef {
         variant: TemperatureUnit::Rankine,
@@ -61,50 +71,115 @@ const UNIT_DEFS: &[UnitDef] = &[
         name: "Rømer",
         aliases: &["ro", "rømer", "romer"],
     },
+    UnitDef {
+        variant: TemperatureUnit::DeltaCelsius,
+        name: "Celsius difference",
+        aliases: &["Δc", "delta-c", "delta-celsius"],
+    },
+    UnitDef {
+        variant: TemperatureUnit::DeltaFahrenheit,
+        name: "Fahrenheit difference",
+        aliases: &["Δf", "delta-f", "delta-fahrenheit"],
+    },
+    UnitDef {
+        variant: TemperatureUnit::DeltaKelvin,
+        name: "Kelvin difference",
+        aliases: &["Δk", "ΔK", "delta-k", "delta-kelvin"],
+    },
+    UnitDef {
+        variant: TemperatureUnit::DeltaRankine,
+        name: "Rankine difference",
+        aliases: &["Δr", "delta-r", "delta-rankine"],
+    },
+    UnitDef {
+        variant: TemperatureUnit::DeltaDelisle,
+        name: "Delisle difference",
+        aliases: &["Δd", "delta-d", "delta-delisle"],
+    },
+    UnitDef {
+        variant: TemperatureUnit::DeltaNewton,
+        name: "Newton difference",
+        aliases: &["Δn", "delta-n", "delta-newton"],
+    },
+    UnitDef {
+        variant: TemperatureUnit::DeltaReaumur,
+        name: "Réaumur difference",
+        aliases: &["Δre", "delta-re", "delta-réaumur", "delta-reaumur"],
+    },
+    UnitDef {
+        variant: TemperatureUnit::DeltaRomer,
+        name: "Rømer difference",
+        aliases: &["Δro", "delta-ro", "delta-rømer", "delta-romer"],
+    },
 ];
 
-impl_conversion_traits!(TemperatureUnit, UNIT_DEFS);
+impl_conversion_traits!(Temperature, TemperatureUnit, UNIT_DEFS, "temperature", "temp", BaseDimensions::TEMPERATURE);
 
 pub fn help_text() -> String {
     TemperatureUnit::generate_help_text()
 }
 
-impl TemperatureUnit {
-    fn to_celsius(&self, value: f64) -> f64 {
+impl Scaled for TemperatureUnit {
+    fn factor(self) -> f64 {
         use TemperatureUnit::*;
         match self {
-            Celsius => value,
-            Fahrenheit => (value - 32.0) * 5.0 / 9.0,
-            Kelvin => value - 273.15,
-            Rankine => (value - 491.67) * 5.0 / 9.0,
-            Delisle => 100.0 - (value * 2.0 / 3.0),
-            Newton => value * 100.0 / 33.0,
-            Reaumur => value * 5.0 / 4.0,
-            Romer => (value - 7.5) * 40.0 / 21.0,
+            Celsius | DeltaCelsius => 1.0,
+            Fahrenheit | DeltaFahrenheit => 5.0 / 9.0,
+            Kelvin | DeltaKelvin => 1.0,
+            Rankine | DeltaRankine => 5.0 / 9.0,
+            Delisle | DeltaDelisle => -2.0 / 3.0,
+            Newton | DeltaNewton => 100.0 / 33.0,
+            Reaumur | DeltaReaumur => 5.0 / 4.0,
+            Romer | DeltaRomer => 40.0 / 21.0,
         }
     }
 
-    fn from_celsius(&self, value: f64) -> f64 {
+    fn zero(self) -> f64 {
         use TemperatureUnit::*;
         match self {
-            Celsius => value,
-            Fahrenheit => value * 9.0 / 5.0 + 32.0,
-            Kelvin => value + 273.15,
-            Rankine => (value + 273.15) * 9.0 / 5.0,
-            Delisle => (100.0 - value) * 3.0 / 2.0,
-            Newton => value * 33.0 / 100.0,
-            Reaumur => value * 4.0 / 5.0,
-            Romer => value * 21.0 / 40.0 + 7.5,
+            Celsius => 0.0,
+            Fahrenheit => 32.0,
+            Kelvin => 273.15,
+            Rankine => 491.67,
+            Delisle => 150.0,
+            Newton => 0.0,
+            Reaumur => 0.0,
+            Romer => 7.5,
+            DeltaCelsius | DeltaFahrenheit | DeltaKelvin | DeltaRankine | DeltaDelisle | DeltaNewton | DeltaReaumur
+            | DeltaRomer => 0.0,
         }
     }
+
+    fn exact_factor(self) -> Option<Exact> {
+        use TemperatureUnit::*;
+        let (numer, denom) = match self {
+            Celsius | Kelvin | DeltaCelsius | DeltaKelvin => (1, 1),
+            Fahrenheit | Rankine | DeltaFahrenheit | DeltaRankine => (5, 9),
+            Delisle | DeltaDelisle => (-2, 3),
+            Newton | DeltaNewton => (100, 33),
+            Reaumur | DeltaReaumur => (5, 4),
+            Romer | DeltaRomer => (40, 21),
+        };
+        Some(Exact::fraction(numer, denom))
+    }
+
+    fn is_difference(self) -> bool {
+        use TemperatureUnit::*;
+        matches!(
+            self,
+            DeltaCelsius | DeltaFahrenheit | DeltaKelvin | DeltaRankine | DeltaDelisle | DeltaNewton | DeltaReaumur | DeltaRomer
+        )
+    }
 }
 
-pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, &'static str> {
+pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, Error> {
     let from: TemperatureUnit = from_unit.parse()?;
     let to: TemperatureUnit = to_unit.parse()?;
 
-    let celsius_value = from.to_celsius(value);
-    let result = to.from_celsius(celsius_value);
-
-    Ok((result * 10000.0).round() / 10000.0)
+    // Differences convert without the offsets of the scales, 10 Δc being 18 Δf
+    if from.is_difference() || to.is_difference() {
+        return Ok(value * from.factor() / to.factor());
+    }
+    let celsius_value = (value - from.zero()) * from.factor();
+    Ok(celsius_value / to.factor() + to.zero())
 }
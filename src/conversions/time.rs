@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use super::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TimeUnit {
     Yoctosecond,
     Zeptosecond,
@@ -29,6 +29,8 @@ pub enum TimeUnit {
     Eon,
 }
 
+pub struct Time;
+
 pub struct UnitDef {
     variant: TimeUnit,
     name: &'static str,
@@ -61,14 +63,14 @@ const UNIT_DEFS: &[UnitDef] = &[
     UnitDef { variant: TimeUnit::Eon, name: "Eon", aliases: &["eon", "eons", "aeon", "aeons"] },
 ];
 
-impl_conversion_traits!(TimeUnit, UNIT_DEFS);
+impl_conversion_traits!(Time, TimeUnit, UNIT_DEFS, "time", "time", BaseDimensions::TIME);
 
 pub fn help_text() -> String {
     TimeUnit::generate_help_text()
 }
 
-impl TimeUnit {
-    fn factor(&self) -> f64 {
+impl Scaled for TimeUnit {
+    fn factor(self) -> f64 {
         use TimeUnit::*;
         
         match self {
@@ -97,12 +99,29 @@ impl TimeUnit {
             Eon => 500e6 * 365.25 * 24.0 * 60.0 * 60.0,
         }
     }
+
+    fn systems(self) -> &'static [System] {
+        use TimeUnit::*;
+
+        match self {
+            Yoctosecond | Zeptosecond | Attosecond | Femtosecond | Picosecond | Nanosecond | Microsecond
+            | Millisecond | Second | Minute | Hour | Day | Year | Megayear | Gigayear | Terayear => &[System::Metric],
+            _ => &[],
+        }
+    }
+
+    fn prefixes(self) -> Prefixes {
+        use TimeUnit::*;
+        match self {
+            Second => Prefixes::Si,
+            _ => Prefixes::None,
+        }
+    }
 }
 
-pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, &'static str> {
+pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, Error> {
     let from: TimeUnit = from_unit.parse()?;
     let to: TimeUnit = to_unit.parse()?;
 
-    let result = value * from.factor() / to.factor();
-    Ok((result * 10000.0).round() / 10000.0)
+    Ok(value * from.factor() / to.factor())
 }
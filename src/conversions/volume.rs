@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use super::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum VolumeUnit {
     CubicYoctometer,
     CubicZeptometer,
@@ -62,6 +62,8 @@ pub enum VolumeUnit {
     Stere,
 }
 
+pub struct Volume;
+
 pub struct UnitDef {
     variant: VolumeUnit,
     name: &'static str,
@@ -127,14 +129,14 @@ const UNIT_DEFS: &[UnitDef] = &[
     UnitDef { variant: VolumeUnit::Stere, name: "Stere", aliases: &["st", "stere", "cubic meter"] },
 ];
 
-impl_conversion_traits!(VolumeUnit, UNIT_DEFS);
+impl_conversion_traits!(Volume, VolumeUnit, UNIT_DEFS, "volume", "vol", BaseDimensions::VOLUME);
 
 pub fn help_text() -> String {
     VolumeUnit::generate_help_text()
 }
 
-impl VolumeUnit {
-    fn factor(&self) -> f64 {
+impl Scaled for VolumeUnit {
+    fn factor(self) -> f64 {
         use VolumeUnit::*;
 
         match self {
@@ -196,12 +198,38 @@ impl VolumeUnit {
             AcreFoot => 1233.48183754752,
         }
     }
+
+    fn exact_factor(self) -> Option<Exact> {
+        use VolumeUnit::*;
+        // The table rounds these, their definitions are exact
+        match self {
+            CubicFoot => Exact::decimal(0.028316846592),
+            CubicYard => Exact::decimal(0.764554857984),
+            CubicMile => {
+                let mile = Exact::decimal(1609.344)?;
+                Some(mile.times(mile).times(mile))
+            }
+            USFluidOunce => Exact::decimal(0.0000295735295625),
+            USTablespoon => Exact::decimal(0.00001478676478125),
+            USTeaspoon => Exact::decimal(0.00000492892159375),
+            UKTablespoon => Exact::decimal(0.0000177581640625),
+            UKTeaspoon => Some(Exact::decimal(0.0000284130625)?.times(Exact::fraction(5, 24))),
+            unit => Exact::decimal(unit.factor()),
+        }
+    }
+
+    fn prefixes(self) -> Prefixes {
+        use VolumeUnit::*;
+        match self {
+            Liter => Prefixes::Si,
+            _ => Prefixes::None,
+        }
+    }
 }
 
-pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, &'static str> {
+pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, Error> {
     let from: VolumeUnit = from_unit.parse()?;
     let to: VolumeUnit = to_unit.parse()?;
 
-    let result = value * from.factor() / to.factor();
-    Ok((result * 10000.0).round()/10000.0)
+    Ok(value * from.factor() / to.factor())
 }
@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use super::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum WeightUnit {
     Microgram,
     Milligram,
@@ -35,6 +35,8 @@ pub enum WeightUnit {
     Momme,
 }
 
+pub struct Weight;
+
 pub struct UnitDef {
     variant: WeightUnit,
     name: &'static str,
@@ -189,14 +191,14 @@ const UNIT_DEFS: &[UnitDef] = &[
     },
 ];
 
-impl_conversion_traits!(WeightUnit, UNIT_DEFS);
+impl_conversion_traits!(Weight, WeightUnit, UNIT_DEFS, "weight", "weight", BaseDimensions::MASS, 1e-3);
 
 pub fn help_text() -> String {
     WeightUnit::generate_help_text()
 }
 
-impl WeightUnit {
-    fn factor(&self) -> f64 {
+impl Scaled for WeightUnit {
+    fn factor(self) -> f64 {
         use WeightUnit::*;
 
         match self {
@@ -231,12 +233,50 @@ impl WeightUnit {
             Momme => 3.75,
         }
     }
+
+    fn systems(self) -> &'static [System] {
+        use WeightUnit::*;
+
+        match self {
+            Microgram | Milligram | Gram | Kilogram | MetricTon => &[System::Metric],
+            Ounce | Pound => &[System::Imperial],
+            _ => &[],
+        }
+    }
+
+    fn exact_factor(self) -> Option<Exact> {
+        use WeightUnit::*;
+        // The table rounds the imperial units, which are defined from the pound of 453.59237 g
+        match self {
+            Ounce => Exact::decimal(28.349523125),
+            Pound => Exact::decimal(453.59237),
+            Stone => Exact::decimal(6350.29318),
+            ImperialTon => Exact::decimal(1016046.9088),
+            Grain => Exact::decimal(0.06479891),
+            Dram => Exact::decimal(1.7718451953125),
+            Cwt => Exact::decimal(50802.34544),
+            Pennyweight => Exact::decimal(1.55517384),
+            TroyOunce => Exact::decimal(31.1034768),
+            Scruple => Exact::decimal(1.2959782),
+            Tola => Exact::decimal(11.6638038),
+            // Defined from standard gravity and the foot, but rounded here
+            Slug => None,
+            unit => Exact::decimal(unit.factor()),
+        }
+    }
+
+    fn prefixes(self) -> Prefixes {
+        use WeightUnit::*;
+        match self {
+            Gram => Prefixes::Si,
+            _ => Prefixes::None,
+        }
+    }
 }
 
-pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, &'static str> {
+pub fn convert(value: f64, from_unit: &str, to_unit: &str) -> Result<f64, Error> {
     let from: WeightUnit = from_unit.parse()?;
     let to: WeightUnit = to_unit.parse()?;
 
-    let result = value * from.factor() / to.factor();
-    Ok((result * 10000.0).round() / 10000.0)
+    Ok(value * from.factor() / to.factor())
 }
@@ -0,0 +1,227 @@
+use std::fs;
+use std::io::{self, Read};
+
+use crate::error::Error;
+use crate::output::{format_result, NumberStyle};
+use crate::{convert_units, Conversion, CsvArgs};
+
+/// A record of a CSV file, its fields kept as written so that quoting survives
+struct Record {
+    fields: Vec<String>,
+    /// Line of the file on which the record starts
+    line: usize,
+}
+
+/// Converts one column of a CSV file and prints the file with the results.
+///
+/// Values are rewritten in place, along with the unit column if there is one, unless `--append`
+/// asks for a new column. A rewritten column named after its unit, such as `weight_lb`, is
+/// renamed after the new one; other names are kept. Every other field is written back exactly as read. Empty cells are left
+/// alone; a cell that fails is reported on stderr with its line number and left unchanged.
+///
+/// Returns the exit code of the first failing row, or 0 when every row was converted.
+pub fn run(args: &CsvArgs, style: &NumberStyle) -> Result<i32, Error> {
+    let mut input = String::new();
+    if args.input == "-" {
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| Error::Io(format!("can't read stdin: {}", e)))?;
+    } else {
+        input = fs::read_to_string(&args.input).map_err(|e| Error::Io(format!("can't read '{}': {}", args.input, e)))?;
+    }
+
+    let line_ending = if input.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut records = parse(&input, args.delimiter).into_iter();
+    let Some(mut header) = records.next() else {
+        return Ok(0);
+    };
+
+    let column = find_column(&header, &args.column)?;
+    let unit_column = args.unit_column.as_deref().map(|name| find_column(&header, name)).transpose()?;
+    match (&args.append, &args.from, unit_column) {
+        (Some(name), _, _) => header.fields.push(quote_like(name, "", args.delimiter)),
+        (None, Some(from), None) => {
+            let name = &header.fields[column];
+            if let Some(renamed) = rename(&unquote(name), from, &args.to) {
+                header.fields[column] = quote_like(&renamed, name, args.delimiter);
+            }
+        }
+        _ => {}
+    }
+    print!("{}{}", join(&header, args.delimiter), line_ending);
+
+    let mut exit_code = 0;
+    for mut record in records {
+        if record.fields == [""] {
+            print!("{}", line_ending);
+            continue;
+        }
+
+        let raw = record.fields.get(column).cloned().unwrap_or_default();
+        let unit = match unit_column {
+            Some(index) => unquote(record.fields.get(index).map_or("", String::as_str)),
+            None => args.from.clone().unwrap_or_default(),
+        };
+
+        let converted = match convert_cell(&unquote(&raw), unit.trim(), &args.to, style) {
+            Ok(conversion) => conversion.map(|conversion| format_result(&conversion, style)),
+            Err(e) => {
+                eprintln!("line {}: {}", record.line, e);
+                if exit_code == 0 {
+                    exit_code = e.exit_code();
+                }
+                None
+            }
+        };
+
+        match (&args.append, converted) {
+            (Some(_), converted) => {
+                // Short rows are padded so that the new column lines up
+                record.fields.resize(header.fields.len() - 1, String::new());
+                record.fields.push(quote_like(&converted.unwrap_or_default(), "", args.delimiter));
+            }
+            (None, Some(converted)) => {
+                record.fields[column] = quote_like(&converted, &raw, args.delimiter);
+                if let Some(index) = unit_column {
+                    let unit = &record.fields[index];
+                    record.fields[index] = quote_like(&args.to, unit, args.delimiter);
+                }
+            }
+            (None, None) => {}
+        }
+        print!("{}{}", join(&record, args.delimiter), line_ending);
+    }
+
+    Ok(exit_code)
+}
+
+/// Converts a cell, giving `None` for an empty one
+fn convert_cell(value: &str, from: &str, to: &str, style: &NumberStyle) -> Result<Option<Conversion>, Error> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(convert_units(&style.locale().parse_number(value)?, from, to)?.finite()?))
+}
+
+/// The name of a column after its unit is converted, for names ending in the unit such as
+/// `weight_lb`, `weight lb` or `weight (lb)`
+fn rename(name: &str, from: &str, to: &str) -> Option<String> {
+    let (name, closing) = match name.strip_suffix([')', ']']) {
+        Some(inner) => (inner, &name[inner.len()..]),
+        None => (name, ""),
+    };
+    let start = name.len().checked_sub(from.len()).filter(|&start| name.is_char_boundary(start))?;
+    let (stem, unit) = name.split_at(start);
+    let separated = stem.ends_with(['_', '-', ' ', '(', '[']);
+    (separated && unit.eq_ignore_ascii_case(from)).then(|| format!("{}{}{}", stem, to, closing))
+}
+
+fn find_column(header: &Record, name: &str) -> Result<usize, Error> {
+    header
+        .fields
+        .iter()
+        .position(|field| unquote(field).trim() == name)
+        .ok_or_else(|| Error::MissingColumn(name.to_string()))
+}
+
+fn join(record: &Record, delimiter: char) -> String {
+    record.fields.join(&delimiter.to_string())
+}
+
+/// Splits CSV text into records, allowing delimiters, quotes and line breaks in quoted fields
+fn parse(input: &str, delimiter: char) -> Vec<Record> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut start = 1;
+
+    for c in input.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                field.push(c);
+            }
+            '\n' if !in_quotes => {
+                if field.ends_with('\r') {
+                    field.pop();
+                }
+                fields.push(std::mem::take(&mut field));
+                records.push(Record { fields: std::mem::take(&mut fields), line: start });
+                line += 1;
+                start = line;
+            }
+            c if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut field)),
+            c => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push(Record { fields, line: start });
+    }
+
+    records
+}
+
+fn unquote(field: &str) -> String {
+    match field.strip_prefix('"').and_then(|field| field.strip_suffix('"')) {
+        Some(inner) => inner.replace("\"\"", "\""),
+        None => field.to_string(),
+    }
+}
+
+/// Quotes `value` if the field it replaces was quoted, or if it has to be
+fn quote_like(value: &str, original: &str, delimiter: char) -> String {
+    if original.starts_with('"') || value.contains(['"', '\n', delimiter]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(input: &str, delimiter: char) -> Vec<Vec<String>> {
+        parse(input, delimiter).into_iter().map(|record| record.fields).collect()
+    }
+
+    #[test]
+    fn parses_records() {
+        assert_eq!(fields("a,b\n1,2\n", ','), [["a", "b"], ["1", "2"]]);
+        assert_eq!(fields("a;b\r\n1;2", ';'), [["a", "b"], ["1", "2"]]);
+        assert_eq!(fields("a,b\n\n1,2\n", ','), [vec!["a", "b"], vec![""], vec!["1", "2"]]);
+    }
+
+    #[test]
+    fn keeps_quoted_fields_as_written() {
+        let records = parse("name,note\n\"Smith, J\",\"two\nlines\"\nx,\"say \"\"hi\"\"\"\n", ',');
+        assert_eq!(records[1].fields, ["\"Smith, J\"", "\"two\nlines\""]);
+        assert_eq!(records[2].line, 4);
+        assert_eq!(unquote(&records[2].fields[1]), "say \"hi\"");
+    }
+
+    #[test]
+    fn quotes_like_the_original() {
+        assert_eq!(quote_like("4.5", "\"10\"", ','), "\"4.5\"");
+        assert_eq!(quote_like("4,5", "10", ','), "\"4,5\"");
+        assert_eq!(quote_like("4,5", "10", ';'), "4,5");
+    }
+
+    #[test]
+    fn renames_columns_named_after_their_unit() {
+        assert_eq!(rename("weight_lb", "lb", "kg").as_deref(), Some("weight_kg"));
+        assert_eq!(rename("mass (lb)", "lb", "kg").as_deref(), Some("mass (kg)"));
+        assert_eq!(rename("Distance KM", "km", "mi").as_deref(), Some("Distance mi"));
+        assert_eq!(rename("bulb", "lb", "kg"), None);
+        assert_eq!(rename("lb", "lb", "kg"), None);
+    }
+}
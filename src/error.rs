@@ -0,0 +1,173 @@
+use std::fmt;
+
+use crate::registry::Dimension;
+
+/// Exit code used for failures outside of conversions, e.g. an unusable terminal.
+pub const EXIT_FAILURE: i32 = 1;
+/// Exit code used when the command line could not be understood.
+pub const EXIT_USAGE: i32 = 2;
+/// Exit code used when a unit (or currency code) is not recognised.
+pub const EXIT_UNKNOWN_UNIT: i32 = 3;
+/// Exit code used when two units cannot be converted into each other.
+pub const EXIT_INCOMPATIBLE_UNITS: i32 = 4;
+/// Exit code used when a numerical value could not be parsed.
+pub const EXIT_INVALID_VALUE: i32 = 5;
+/// Exit code used when exchange rates could not be fetched.
+pub const EXIT_NETWORK: i32 = 6;
+/// Exit code used when the local rate cache could not be read or written.
+pub const EXIT_CACHE: i32 = 7;
+/// Exit code used when units match several categories and none was named.
+pub const EXIT_AMBIGUOUS_UNITS: i32 = 8;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The unit is not part of the given category
+    UnknownUnit {
+        category: &'static str,
+        input: String,
+        suggestions: Vec<String>,
+        /// Name and subcommand of another category the unit belongs to
+        elsewhere: Option<(&'static str, &'static str)>,
+    },
+    /// A compound unit is malformed, e.g. `(m` or `m^2^`, or its exponents are out of range
+    InvalidUnit { unit: String, problem: String },
+    /// Both units exist but measure different things
+    IncompatibleUnits { from: String, to: String },
+    /// Compound units whose base dimensions differ
+    DimensionMismatch {
+        from: String,
+        from_dimensions: String,
+        to: String,
+        to_dimensions: String,
+    },
+    /// Both units exist in several categories, given as subcommands
+    AmbiguousUnits {
+        from: String,
+        to: String,
+        candidates: Vec<&'static str>,
+    },
+    /// A required command line argument was not given
+    MissingArgument(&'static str),
+    /// A CSV file has no column of this name
+    MissingColumn(String),
+    /// An option that does not apply to the command, e.g. `--format json` for `cnv csv`
+    UnusedOption { option: &'static str, command: &'static str },
+    /// The value could not be parsed as a number
+    ParseValue(String),
+    /// The locale given with `--locale` is not known
+    UnknownLocale(String),
+    /// A conversion gives a result too large for `f64`, e.g. 1e300 m in nm
+    OutOfRange { value: String, to: String },
+    /// An expression could not be parsed, holding the part from where it went wrong
+    InvalidExpression(String),
+    /// Exchange rates could not be downloaded
+    Network(String),
+    /// The exchange rate provider answered with something unexpected
+    InvalidResponse(String),
+    /// The exchange rate cache could not be used
+    Cache(String),
+    /// A setting from the environment or the config file is not valid
+    Config(String),
+    /// Reading from or writing to the terminal failed
+    Io(String),
+}
+
+impl Error {
+    pub fn unknown_unit(category: &'static str, input: &str, suggestions: Vec<String>) -> Self {
+        Error::UnknownUnit {
+            category,
+            input: input.to_string(),
+            suggestions,
+            elsewhere: None,
+        }
+    }
+
+    /// Points an unknown unit error to the category the unit does belong to
+    pub fn elsewhere(self, dimension: Option<&dyn Dimension>) -> Self {
+        match self {
+            Error::UnknownUnit { category, input, suggestions, .. } => Error::UnknownUnit {
+                category,
+                input,
+                suggestions,
+                elsewhere: dimension.map(|dimension| (dimension.name(), dimension.command())),
+            },
+            error => error,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::UnknownUnit { .. } | Error::InvalidUnit { .. } => EXIT_UNKNOWN_UNIT,
+            Error::IncompatibleUnits { .. } | Error::DimensionMismatch { .. } => EXIT_INCOMPATIBLE_UNITS,
+            Error::AmbiguousUnits { .. } => EXIT_AMBIGUOUS_UNITS,
+            Error::MissingArgument(_)
+            | Error::MissingColumn(_)
+            | Error::UnusedOption { .. }
+            | Error::UnknownLocale(_)
+            | Error::Config(_) => EXIT_USAGE,
+            Error::ParseValue(_) | Error::OutOfRange { .. } | Error::InvalidExpression(_) => EXIT_INVALID_VALUE,
+            Error::Network(_) | Error::InvalidResponse(_) => EXIT_NETWORK,
+            Error::Cache(_) => EXIT_CACHE,
+            Error::Io(_) => EXIT_FAILURE,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownUnit { category, input, suggestions, elsewhere } => {
+                if category.is_empty() {
+                    write!(f, "Invalid unit '{}'.", input)?;
+                } else {
+                    write!(f, "Invalid {} unit '{}'.", category, input)?;
+                }
+                if !suggestions.is_empty() {
+                    write!(f, "\nDid you mean: {}?", suggestions.join(", "))?;
+                }
+                if let Some((name, command)) = elsewhere {
+                    let article = if name.starts_with(['a', 'e', 'i', 'o', 'u']) { "an" } else { "a" };
+                    return write!(f, "\n'{}' is {} {} unit, try `cnv {}`", input, article, name, command);
+                }
+                write!(f, "\nUse the --list (or) -L flag on a command to see the list of supported units\nUsage: cnv <COMMAND> --list")
+            }
+            Error::InvalidUnit { unit, problem } => write!(f, "Invalid unit '{}': {}", unit, problem),
+            Error::IncompatibleUnits { from, to } => {
+                write!(f, "Cannot convert '{}' to '{}': the units measure different things", from, to)
+            }
+            Error::DimensionMismatch { from, from_dimensions, to, to_dimensions } => {
+                write!(
+                    f,
+                    "Cannot convert '{}' to '{}': dimensions {} and {} do not match",
+                    from, to, from_dimensions, to_dimensions
+                )
+            }
+            Error::AmbiguousUnits { from, to, candidates } => {
+                write!(
+                    f,
+                    "'{}' and '{}' are units of several categories: {}.\nName the category to use, e.g. cnv {} <VALUE> {} {}",
+                    from,
+                    to,
+                    candidates.join(", "),
+                    candidates.first().unwrap_or(&"<COMMAND>"),
+                    from,
+                    to
+                )
+            }
+            Error::MissingArgument(arg) => write!(f, "{} required", arg),
+            Error::MissingColumn(name) => write!(f, "No column named '{}'", name),
+            Error::UnusedOption { option, command } => write!(f, "{} can not be used with `cnv {}`", option, command),
+            Error::ParseValue(input) => write!(f, "Invalid value '{}'", input),
+            Error::OutOfRange { value, to } => write!(f, "{} is out of range in {}", value, to),
+            Error::UnknownLocale(locale) => write!(f, "Unknown locale '{}', e.g. en, de, fr or de-CH", locale),
+            Error::InvalidExpression(rest) => write!(f, "Invalid expression at '{}'", rest),
+            Error::Network(msg) => write!(f, "Network error: {}", msg),
+            Error::InvalidResponse(msg) => write!(f, "Invalid response from rate provider: {}", msg),
+            Error::Cache(msg) => write!(f, "Cache error: {}", msg),
+            Error::Config(msg) => write!(f, "Invalid setting: {}", msg),
+            Error::Io(msg) => write!(f, "I/O error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
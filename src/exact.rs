@@ -0,0 +1,209 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Pow, Signed, Zero};
+
+/// Significant digits up to which a factor is taken to be written as an exact decimal.
+/// Factors computed in `f64`, like `5.0 / 9.0`, come out with 16 or 17 digits.
+const MAX_FACTOR_DIGITS: usize = 15;
+
+/// A fraction times a power of ten, the exact size of a unit defined in terms of another.
+///
+/// It stays small enough to be copied around with its unit, and turns into a [`BigRational`]
+/// for the arithmetic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Exact {
+    numer: i128,
+    denom: i128,
+    exponent: i32,
+}
+
+impl Exact {
+    pub(crate) const fn fraction(numer: i128, denom: i128) -> Self {
+        Exact { numer, denom, exponent: 0 }
+    }
+
+    /// Ten to the given power, the size of an SI prefix
+    pub(crate) const fn power_of_ten(exponent: i32) -> Self {
+        Exact { numer: 1, denom: 1, exponent }
+    }
+
+    /// Reads a factor as the decimal it was written as, e.g. `0.3048` as 3048/10000.
+    ///
+    /// Returns `None` for factors with more digits than a written decimal would have, which
+    /// are the result of a division and need to be given as a fraction instead.
+    pub(crate) fn decimal(value: f64) -> Option<Self> {
+        Self::from_f64(value, MAX_FACTOR_DIGITS)
+    }
+
+    /// Divides by a whole number, as in `1609.344 / 3600.0`
+    pub(crate) fn over(self, denom: i128) -> Self {
+        Exact { denom: self.denom * denom, ..self }
+    }
+
+    /// Multiplies by another exact factor, as in `0.3048 * 4.4482216152605`
+    pub(crate) fn times(self, other: Exact) -> Self {
+        Exact {
+            numer: self.numer * other.numer,
+            denom: self.denom * other.denom,
+            exponent: self.exponent + other.exponent,
+        }
+    }
+
+    pub(crate) fn to_rational(self) -> BigRational {
+        let power = BigInt::from(10).pow(self.exponent.unsigned_abs());
+        let value = BigRational::new(BigInt::from(self.numer), BigInt::from(self.denom));
+        if self.exponent >= 0 {
+            value * power
+        } else {
+            value / power
+        }
+    }
+
+    fn from_f64(value: f64, max_digits: usize) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+
+        // The shortest way of writing the value that reads back the same, e.g. "3.048e-1"
+        let written = format!("{:e}", value);
+        let (mantissa, exponent) = written.split_once('e')?;
+        let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let digits = format!("{}{}", whole, fraction);
+        if digits.trim_start_matches('-').len() <= max_digits {
+            let exponent: i32 = exponent.parse().ok()?;
+            return Some(Exact { numer: digits.parse().ok()?, denom: 1, exponent: exponent - fraction.len() as i32 });
+        }
+
+        // Powers of two such as 2^80 for the yobibit are whole numbers with too many digits
+        if value.fract() == 0.0 && value.abs() < i128::MAX as f64 {
+            return Some(Exact::fraction(value as i128, 1));
+        }
+        None
+    }
+}
+
+/// Reads a value as the decimal it was written as, e.g. 0.1 as 1/10
+pub(crate) fn value(value: f64) -> Option<BigRational> {
+    Exact::from_f64(value, 17).map(Exact::to_rational)
+}
+
+/// Whether the fraction has a finite decimal expansion, i.e. its denominator only has the
+/// factors 2 and 5
+pub(crate) fn is_terminating(value: &BigRational) -> bool {
+    let mut denom = value.denom().clone();
+    for factor in [2, 5] {
+        let factor = BigInt::from(factor);
+        while (&denom % &factor).is_zero() {
+            denom /= &factor;
+        }
+    }
+    denom.is_one()
+}
+
+/// Writes the value with `decimals` places, rounding half away from zero. Negative `decimals`
+/// round to tens, hundreds and so on.
+pub(crate) fn to_decimal(value: &BigRational, decimals: i32) -> String {
+    let scale = BigRational::from_integer(BigInt::from(10).pow(decimals.unsigned_abs()));
+    let scaled = if decimals >= 0 { value.abs() * &scale } else { value.abs() / &scale };
+    let rounded = scaled.round().to_integer();
+
+    let sign = if value.is_negative() && !rounded.is_zero() { "-" } else { "" };
+    if decimals <= 0 {
+        let whole = if decimals == 0 { rounded } else { rounded * scale.to_integer() };
+        return format!("{}{}", sign, whole);
+    }
+    let decimals = decimals as usize;
+    let digits = format!("{:0>width$}", rounded.to_string(), width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    format!("{}{}.{}", sign, whole, fraction)
+}
+
+/// Number of decimal places after which a terminating fraction ends
+pub(crate) fn decimal_places(value: &BigRational) -> i32 {
+    let mut places = 0;
+    let mut scaled = value.clone();
+    while !scaled.is_integer() {
+        scaled *= BigRational::from_integer(BigInt::from(10));
+        places += 1;
+    }
+    places
+}
+
+/// The power of ten of the value's leading digit, e.g. 2 for 123.4 and -3 for 0.00123
+pub(crate) fn magnitude(value: &BigRational) -> i32 {
+    let value = value.abs();
+    if value.is_zero() {
+        return 0;
+    }
+    let ten = BigRational::from_integer(BigInt::from(10));
+    let mut exponent = 0;
+    let mut power = BigRational::one();
+    if value >= power {
+        while value >= &power * &ten {
+            power *= &ten;
+            exponent += 1;
+        }
+    } else {
+        while value < power {
+            power /= &ten;
+            exponent -= 1;
+        }
+    }
+    exponent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::registry;
+
+    fn rational(numerator: i64, denominator: i64) -> BigRational {
+        BigRational::new(numerator.into(), denominator.into())
+    }
+
+    fn convert(category: &str, from: &str, to: &str) -> BigRational {
+        let registry = registry();
+        let (from, to) = (registry.find_in(category, from).unwrap(), registry.find_in(category, to).unwrap());
+        from.convert(BigRational::one(), &to).unwrap()
+    }
+
+    #[test]
+    fn reads_factors_as_written() {
+        assert_eq!(Exact::decimal(0.3048).map(Exact::to_rational), Some(rational(3048, 10000)));
+        assert_eq!(Exact::decimal(5.0 / 9.0), None);
+        assert_eq!(Exact::fraction(5, 9).to_rational(), rational(5, 9));
+        assert_eq!(Exact::power_of_ten(-3).to_rational(), rational(1, 1000));
+        assert_eq!(Exact::decimal(1609.344).unwrap().over(3600).to_rational(), rational(1609344, 3600000));
+    }
+
+    #[test]
+    fn converts_with_exact_factors() {
+        assert_eq!(convert("distance", "ft", "m"), rational(3048, 10000));
+        assert_eq!(convert("time", "month", "d"), rational(304375, 10000));
+        assert_eq!(convert("temperature", "f", "c"), rational(-155, 9));
+    }
+
+    #[test]
+    fn keeps_whole_data_sizes_exact() {
+        assert_eq!(convert("data storage", "YiB", "b").to_string(), "9671406556917033397649408");
+    }
+
+    #[test]
+    fn writes_decimals() {
+        assert!(is_terminating(&rational(3, 8)));
+        assert!(!is_terminating(&rational(1, 3)));
+        assert_eq!(decimal_places(&rational(3, 8)), 3);
+        assert_eq!(to_decimal(&rational(3, 8), 2), "0.38");
+        assert_eq!(to_decimal(&rational(-1, 8), 2), "-0.13");
+        assert_eq!(to_decimal(&rational(-1, 1000), 2), "0.00");
+        assert_eq!(to_decimal(&rational(12345, 1), -2), "12300");
+    }
+
+    #[test]
+    fn finds_magnitudes() {
+        assert_eq!(magnitude(&rational(1234, 10)), 2);
+        assert_eq!(magnitude(&rational(123, 100000)), -3);
+        assert_eq!(magnitude(&rational(1, 1)), 0);
+        assert_eq!(magnitude(&BigRational::zero()), 0);
+    }
+}
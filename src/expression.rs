@@ -0,0 +1,442 @@
+use std::ops::Range;
+
+use crate::compound::{BaseDimensions, CompoundUnit};
+use crate::error::Error;
+use crate::registry::registry;
+
+/// The result of an expression such as `5 ft + 3 in to cm`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    /// The expression without its target unit
+    pub expression: String,
+    pub value: f64,
+    /// The target unit, or the first unit of the expression with the same dimensions
+    pub unit: String,
+    pub dimensions: BaseDimensions,
+}
+
+impl Evaluation {
+    /// Name of the registered category measuring the result, if any
+    pub fn category(&self) -> Option<&'static str> {
+        registry()
+            .dimensions()
+            .find(|dimension| dimension.base_dimensions() == self.dimensions)
+            .map(|dimension| dimension.name())
+    }
+}
+
+/// Evaluates quantities combined with `+ - * /` and parentheses, with an optional
+/// trailing `to <unit>`. Quantities written side by side add up, as in `5 ft 11 in`.
+///
+/// Temperatures are treated as differences, so `10 c + 5 c` is 15 degrees.
+pub fn evaluate(input: &str) -> Result<Evaluation, Error> {
+    let tokens = tokenize(input)?;
+
+    let (tokens, target) = match tokens.iter().rposition(|(token, _)| *token == Token::Word("to".to_string())) {
+        Some(index) => {
+            let target = tokens.get(index + 1).map(|(_, span)| input[span.start..].trim());
+            let target = target.ok_or(Error::MissingArgument("Target unit after 'to'"))?;
+            (&tokens[..index], Some(CompoundUnit::parse(target)?))
+        }
+        None => (&tokens[..], None),
+    };
+
+    let mut parser = Parser { tokens, position: 0, input, units: Vec::new(), end: 0 };
+    let quantity = parser.sum()?;
+    if let Some((_, span)) = tokens.get(parser.position) {
+        return Err(Error::InvalidExpression(input[span.start..].trim().to_string()));
+    }
+
+    let unit = match target {
+        Some(target) => target,
+        None => parser
+            .units
+            .into_iter()
+            .find(|unit| unit.dimensions == quantity.dimensions)
+            .unwrap_or_else(|| CompoundUnit {
+                expression: coherent_unit(quantity.dimensions),
+                factor: 1.0,
+                dimensions: quantity.dimensions,
+            }),
+    };
+
+    if unit.dimensions != quantity.dimensions {
+        return Err(Error::DimensionMismatch {
+            from: input[..parser.end].trim().to_string(),
+            from_dimensions: quantity.dimensions.to_string(),
+            to: unit.expression,
+            to_dimensions: unit.dimensions.to_string(),
+        });
+    }
+
+    Ok(Evaluation {
+        expression: input[..parser.end].trim().to_string(),
+        value: quantity.value / unit.factor,
+        unit: unit.expression,
+        dimensions: quantity.dimensions,
+    })
+}
+
+fn coherent_unit(dimensions: BaseDimensions) -> String {
+    if dimensions == BaseDimensions::NONE {
+        String::new()
+    } else {
+        dimensions.to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Word(String),
+    Plus,
+    Minus,
+    Mul,
+    Div,
+    Open,
+    Close,
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, Range<usize>)>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '+' => Token::Plus,
+            '-' | '−' => Token::Minus,
+            '*' | '×' | '·' => Token::Mul,
+            '/' | '÷' => Token::Div,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            c if c.is_ascii_digit() || c == '.' => {
+                let end = scan_number(input, start);
+                let number = &input[start..end];
+                let value = number.parse().map_err(|_| Error::ParseValue(number.to_string()))?;
+                while chars.peek().is_some_and(|&(i, _)| i < end) {
+                    chars.next();
+                }
+                tokens.push((Token::Number(value), start..end));
+                continue;
+            }
+            _ => {
+                let end = start + split_mixed(&input[start..scan_word(input, start)]);
+                while chars.peek().is_some_and(|&(i, _)| i < end) {
+                    chars.next();
+                }
+                tokens.push((Token::Word(input[start..end].to_string()), start..end));
+                continue;
+            }
+        };
+        chars.next();
+        tokens.push((token, start..start + c.len_utf8()));
+    }
+
+    Ok(tokens)
+}
+
+/// Finds the end of a number like `12`, `.5` or `1.5e-3`
+fn scan_number(input: &str, start: usize) -> usize {
+    let bytes = input.as_bytes();
+    let mut end = start;
+    while end < bytes.len() && (bytes[end].is_ascii_digit() || bytes[end] == b'.') {
+        end += 1;
+    }
+
+    // An exponent needs digits after it, otherwise the `e` starts a unit such as `eV`
+    if end < bytes.len() && matches!(bytes[end], b'e' | b'E') {
+        let mut exponent = end + 1;
+        if exponent < bytes.len() && matches!(bytes[exponent], b'+' | b'-') {
+            exponent += 1;
+        }
+        if exponent < bytes.len() && bytes[exponent].is_ascii_digit() {
+            end = exponent;
+            while end < bytes.len() && bytes[end].is_ascii_digit() {
+                end += 1;
+            }
+        }
+    }
+    end
+}
+
+/// Finds the end of a unit. Operators inside a word belong to the unit, as in `km/h`
+/// or `BTU/(h*ft^2)`, while a word ends at whitespace, `+` or an unmatched `)`.
+fn scan_word(input: &str, start: usize) -> usize {
+    let mut depth = 0;
+    for (i, c) in input[start..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return start + i,
+            ')' => depth -= 1,
+            c if depth == 0 && (c.is_whitespace() || c == '+') => return start + i,
+            _ => {}
+        }
+    }
+    input.len()
+}
+
+/// Length of the leading unit of a word like `ft10in` or `h30m`, written without spaces between
+/// the parts of a mixed quantity. Words that are units themselves, like `m3`, are kept whole.
+fn split_mixed(word: &str) -> usize {
+    if CompoundUnit::parse(word).is_ok() {
+        return word.len();
+    }
+
+    word.char_indices()
+        .skip(1)
+        .find(|&(i, c)| c.is_ascii_digit() && registry().find(&word[..i]).is_some())
+        .map_or(word.len(), |(i, _)| i)
+}
+
+/// A value in coherent SI units
+#[derive(Debug, Clone)]
+struct Value {
+    value: f64,
+    dimensions: BaseDimensions,
+    /// The number and unit as written, e.g. `30 m`, so that the `m` of `1h30m` can be read as
+    /// minutes
+    written: Option<(f64, String)>,
+}
+
+impl Value {
+    fn number(value: f64) -> Self {
+        Value { value, dimensions: BaseDimensions::NONE, written: None }
+    }
+
+    /// The `m` of `1h30m` read as minutes, which it is only when it follows hours
+    fn minutes_after(&self, left: &Value) -> Option<Value> {
+        let (number, alias) = self.written.as_ref()?;
+        let (_, hours) = left.written.as_ref()?;
+        let hour = registry().find_in("time", "h").ok()?;
+        if alias != "m" || !hour.aliases.contains(&hours.as_str()) {
+            return None;
+        }
+        let minute = registry().find_in("time", "min").ok()?;
+        Some(Value { value: number * minute.si_factor(), dimensions: minute.base_dimensions(), written: None })
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [(Token, Range<usize>)],
+    position: usize,
+    input: &'a str,
+    /// Every unit in the order written, to pick one for the result
+    units: Vec<CompoundUnit>,
+    end: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let (token, span) = self.tokens.get(self.position)?;
+        self.position += 1;
+        self.end = span.end;
+        Some(token)
+    }
+
+    fn start(&self) -> usize {
+        self.tokens.get(self.position).map_or(self.input.len(), |(_, span)| span.start)
+    }
+
+    fn syntax_error(&self) -> Error {
+        match self.tokens.get(self.position) {
+            Some((_, span)) => Error::InvalidExpression(self.input[span.start..].trim().to_string()),
+            None => Error::InvalidExpression(self.input.trim().to_string()),
+        }
+    }
+
+    /// Units multiplied together until an exponent leaves the range of `i8`
+    fn overflow(&self) -> Error {
+        Error::InvalidUnit { unit: self.input.trim().to_string(), problem: "exponents are out of range".to_string() }
+    }
+
+    fn sum(&mut self) -> Result<Value, Error> {
+        let start = self.start();
+        let mut left = self.product()?;
+
+        loop {
+            let sign = match self.peek() {
+                Some(Token::Plus) => 1.0,
+                Some(Token::Minus) => -1.0,
+                // Quantities written side by side add up, as in "5 ft 11 in"
+                Some(Token::Number(_)) => 0.0,
+                _ => return Ok(left),
+            };
+            if sign != 0.0 {
+                self.next();
+            }
+
+            let right_start = self.start();
+            let mut right = self.product()?;
+            if sign == 0.0 {
+                if let Some(minutes) = right.minutes_after(&left) {
+                    right = minutes;
+                }
+            }
+            if left.dimensions != right.dimensions {
+                return Err(Error::DimensionMismatch {
+                    from: self.input[right_start..self.end].trim().to_string(),
+                    from_dimensions: right.dimensions.to_string(),
+                    to: self.input[start..right_start].trim().trim_end_matches(['+', '-', '−']).trim().to_string(),
+                    to_dimensions: left.dimensions.to_string(),
+                });
+            }
+            left.value += if sign < 0.0 { -right.value } else { right.value };
+            left.written = None;
+        }
+    }
+
+    fn product(&mut self) -> Result<Value, Error> {
+        let mut left = self.unary()?;
+
+        loop {
+            match self.peek() {
+                Some(Token::Mul) => {
+                    self.next();
+                    let right = self.unary()?;
+                    left.value *= right.value;
+                    left.dimensions = left.dimensions.checked_mul(right.dimensions).ok_or_else(|| self.overflow())?;
+                    left.written = None;
+                }
+                Some(Token::Div) => {
+                    self.next();
+                    let right = self.unary()?;
+                    left.value /= right.value;
+                    left.dimensions = left.dimensions.checked_div(right.dimensions).ok_or_else(|| self.overflow())?;
+                    left.written = None;
+                }
+                _ => return Ok(left),
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Value, Error> {
+        match self.peek() {
+            Some(Token::Minus) => {
+                self.next();
+                let value = self.unary()?;
+                Ok(Value { value: -value.value, written: None, ..value })
+            }
+            Some(Token::Plus) => {
+                self.next();
+                self.unary()
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Value, Error> {
+        let value = match self.peek().cloned() {
+            Some(Token::Number(number)) => {
+                self.next();
+                Value::number(number)
+            }
+            Some(Token::Open) => {
+                self.next();
+                let inner = self.sum()?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(self.syntax_error());
+                }
+                self.next();
+                inner
+            }
+            // A unit on its own stands for one of it, as in "ft to m"
+            Some(Token::Word(_)) => Value::number(1.0),
+            _ => return Err(self.syntax_error()),
+        };
+
+        match self.unit()? {
+            Some(unit) => Ok(Value {
+                value: value.value * unit.factor,
+                dimensions: value.dimensions.checked_mul(unit.dimensions).ok_or_else(|| self.overflow())?,
+                written: (value.dimensions == BaseDimensions::NONE).then(|| (value.value, unit.expression.clone())),
+            }),
+            None => Ok(value),
+        }
+    }
+
+    /// Reads the words following a number as one unit, so that `fl oz` or `kW h` work
+    fn unit(&mut self) -> Result<Option<CompoundUnit>, Error> {
+        let (start, position) = (self.start(), self.position);
+        while matches!(self.peek(), Some(Token::Word(_))) {
+            self.next();
+        }
+        if self.position == position {
+            return Ok(None);
+        }
+
+        let unit = CompoundUnit::parse(self.input[start..self.end].trim())?;
+        self.units.push(unit.clone());
+        Ok(Some(unit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(input: &str) -> (f64, String) {
+        let evaluation = evaluate(input).unwrap();
+        (evaluation.value, evaluation.unit)
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn converts_sums_to_the_target_unit() {
+        let (cm, unit) = value("5 ft + 3 in to cm");
+        assert!(close(cm, 160.02) && unit == "cm");
+        let (m, unit) = value("5 ft 11 in to m");
+        assert!(close(m, 1.8034) && unit == "m");
+    }
+
+    #[test]
+    fn adds_durations_in_the_first_unit() {
+        let (h, unit) = value("2 h 15 min + 45 min");
+        assert!(close(h, 3.0) && unit == "h");
+    }
+
+    #[test]
+    fn multiplies_by_numbers() {
+        let (cups, _) = value("3 * 250 ml to cup");
+        assert!((cups - 3.1701).abs() < 1e-4);
+    }
+
+    #[test]
+    fn adds_temperatures_as_differences() {
+        let (degrees, unit) = value("10 c + 5 c");
+        assert!(close(degrees, 15.0) && unit == "c");
+    }
+
+    #[test]
+    fn multiplies_dimensions() {
+        let evaluation = evaluate("2 m * 3 m").unwrap();
+        assert!(close(evaluation.value, 6.0));
+        assert_eq!(evaluation.dimensions, BaseDimensions::AREA);
+    }
+
+    #[test]
+    fn rejects_mismatched_sums() {
+        assert!(matches!(evaluate("1 m + 1 s"), Err(Error::DimensionMismatch { .. })));
+    }
+
+    #[test]
+    fn rejects_trailing_operators() {
+        assert!(matches!(evaluate("5 ft +"), Err(Error::InvalidExpression(_))));
+        assert!(matches!(evaluate("5 ft to"), Err(Error::MissingArgument(_))));
+    }
+
+    #[test]
+    fn rejects_exponents_out_of_range() {
+        assert!(matches!(evaluate("(1 m^100) * (1 m^100) to m"), Err(Error::InvalidUnit { .. })));
+    }
+}
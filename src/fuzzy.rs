@@ -0,0 +1,121 @@
+//! Lenient matching of unit names: ignoring case and accents, and suggesting near misses
+
+/// Most suggestions given for a unit that is not known
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Lowercases a unit name and strips its accents, so that `Rømer` and `romer` compare equal
+pub(crate) fn fold(name: &str) -> String {
+    let mut folded = String::with_capacity(name.len());
+    for c in name.chars().flat_map(char::to_lowercase) {
+        match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' => folded.push('a'),
+            'ç' | 'č' | 'ć' => folded.push('c'),
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ě' => folded.push('e'),
+            'ì' | 'í' | 'î' | 'ï' | 'ī' => folded.push('i'),
+            'ñ' | 'ń' | 'ň' => folded.push('n'),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' => folded.push('o'),
+            'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' => folded.push('u'),
+            'ý' | 'ÿ' => folded.push('y'),
+            'š' | 'ś' => folded.push('s'),
+            'ž' | 'ź' | 'ż' => folded.push('z'),
+            'ß' => folded.push_str("ss"),
+            'æ' => folded.push_str("ae"),
+            'œ' => folded.push_str("oe"),
+            // The micro sign and the Greek letter mu, both written for micro
+            'µ' | 'μ' => folded.push('u'),
+            c => folded.push(c),
+        }
+    }
+    folded
+}
+
+/// The one candidate whose names match `input` ignoring case and accents, if only one does.
+///
+/// Candidates are given with their names, e.g. units with their aliases.
+pub(crate) fn unique<'a, T: PartialEq>(candidates: impl IntoIterator<Item = (T, &'a [&'a str])>, input: &str) -> Option<T> {
+    let folded = fold(input);
+    let mut found = None;
+    for (candidate, names) in candidates {
+        if !names.iter().any(|name| fold(name) == folded) {
+            continue;
+        }
+        match &found {
+            None => found = Some(candidate),
+            Some(first) if *first == candidate => {}
+            // Both `Mb` and `MB` match "mb"
+            Some(_) => return None,
+        }
+    }
+    found
+}
+
+/// Names close to `input`, nearest first: those differing only in case or accents, then those
+/// within a few edits, such as `kilometre` for "kilometr"
+pub(crate) fn suggest<'a>(names: impl IntoIterator<Item = &'a str>, input: &str) -> Vec<String> {
+    let folded = fold(input);
+    let limit = match folded.chars().count() {
+        0..=3 => 1,
+        4..=6 => 2,
+        _ => 3,
+    };
+
+    let mut close: Vec<(usize, &str)> = names
+        .into_iter()
+        .filter(|name| *name != input)
+        .map(|name| (distance(&fold(name), &folded), name))
+        .filter(|(distance, _)| *distance <= limit)
+        .collect();
+    close.sort();
+    close.dedup_by(|a, b| a.1 == b.1);
+    close.into_iter().take(MAX_SUGGESTIONS).map(|(_, name)| name.to_string()).collect()
+}
+
+/// Levenshtein distance: the fewest insertions, deletions and substitutions turning `a` into `b`
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KELVIN: &[&str] = &["K", "kelvin", "kelvins"];
+    const ROMER: &[&str] = &["°Rø", "Rømer", "romer"];
+    const MEGABIT: &[&str] = &["Mb", "megabit"];
+    const MEGABYTE: &[&str] = &["MB", "megabyte"];
+
+    #[test]
+    fn finds_the_one_unit_matching() {
+        let units = [("kelvin", KELVIN), ("rømer", ROMER), ("megabit", MEGABIT), ("megabyte", MEGABYTE)];
+        assert_eq!(unique(units, "Kelvin"), Some("kelvin"));
+        assert_eq!(unique(units, "RØMER"), Some("rømer"));
+        assert_eq!(unique(units, "MEGABIT"), Some("megabit"));
+        assert_eq!(unique(units, "parsec"), None);
+    }
+
+    #[test]
+    fn leaves_ambiguous_matches_unresolved() {
+        let units = [("megabit", MEGABIT), ("megabyte", MEGABYTE)];
+        assert_eq!(unique(units, "mb"), None);
+        // The same unit listed twice, e.g. found in two tables, is still one match
+        assert_eq!(unique([("kelvin", KELVIN), ("kelvin", KELVIN)], "KELVIN"), Some("kelvin"));
+    }
+
+    #[test]
+    fn suggests_the_nearest_names_first() {
+        let names = ["m", "mm", "km", "KMM", "mile", "kilometre"];
+        assert_eq!(suggest(names, "kmm"), ["KMM", "km", "mm"]);
+        assert_eq!(suggest(names, "kilometr"), ["kilometre"]);
+        assert!(suggest(names, "parsec").is_empty());
+    }
+}
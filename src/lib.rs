@@ -1,9 +1,34 @@
-use std::process;
-use clap::{Parser, Subcommand, Args};
+use std::ffi::OsString;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Args, CommandFactory, Parser, Subcommand};
 
 use conversions::*;
 
+pub mod batch;
+pub mod compound;
+mod config;
+pub mod csv;
 pub mod conversions;
+pub mod error;
+mod exact;
+pub mod expression;
+mod fuzzy;
+pub mod locale;
+pub mod number;
+pub mod output;
+mod prefix;
+pub mod registry;
+pub mod repl;
+
+pub use compound::{BaseDimensions, CompoundUnit};
+pub use num_rational::BigRational;
+pub use error::Error;
+pub use expression::{evaluate, Evaluation};
+pub use locale::Locale;
+pub use number::{Number, Numeric};
+pub use registry::{registry, Dimension, Quantity, Registry, Unit};
+pub use output::{Format, NumberStyle};
 
 const HELP_TEMPLATE: &str = "
 ----------------------------------------------------------
@@ -27,11 +52,39 @@ const SUBCOMMAND_TEMPLATE: &str = "
 ";
 
 #[derive(Debug, Parser)]
-#[command(version, about, long_about=None, help_template=HELP_TEMPLATE)]
+#[command(
+    version,
+    about,
+    long_about=None,
+    help_template=HELP_TEMPLATE,
+    args_conflicts_with_subcommands=true,
+    arg_required_else_help=true,
+    allow_negative_numbers=true
+)]
 pub struct Cmd {
-    /// Measurement category
+    /// Measurement category, detected from the units when omitted
     #[clap(subcommand)]
-    pub measurement: Measurement,
+    pub measurement: Option<Measurement>,
+    /// The value and units to convert, e.g. `10 km mi`, or an expression such as `5 ft + 3 in to cm`
+    #[arg(value_name = "EXPRESSION")]
+    pub args: Vec<String>,
+    /// Convert every line of FILE (`-` for stdin); the arguments become the units of lines holding only a value
+    #[arg(long, value_name = "FILE")]
+    pub batch: Option<String>,
+    /// Units to convert to, separated by commas, or `all` for every unit of the category
+    #[arg(long = "to", value_name = "UNITS")]
+    pub targets: Option<String>,
+    /// Output format
+    #[arg(long, value_enum, global=true, default_value_t)]
+    pub format: Format,
+    /// Only use cached exchange rates, never fetch new ones
+    #[arg(long, global=true)]
+    pub offline: bool,
+    /// Hours after which cached exchange rates are refreshed
+    #[arg(long, value_name = "HOURS", global=true, env = "CNV_MAX_AGE", default_value_t = 24)]
+    pub max_age: u32,
+    #[command(flatten)]
+    pub style: NumberStyle,
 }
 
 #[derive(Debug, Subcommand)]
@@ -77,79 +130,668 @@ pub enum Measurement {
     Speed(Fields),
     /// Convert between currencies
     #[command(help_template = SUBCOMMAND_TEMPLATE)]
-    Currency(Fields)
+    Currency(Fields),
+    /// Start an interactive session
+    #[command(help_template = SUBCOMMAND_TEMPLATE)]
+    Repl,
+    /// Convert a column of a CSV file
+    #[command(help_template = SUBCOMMAND_TEMPLATE)]
+    Csv(CsvArgs),
 }
 
 #[derive(Debug, Args)]
+#[command(allow_negative_numbers=true)]
 pub struct Fields {
-    /// The numerical value to convert
+    /// The numerical value to convert, e.g. 1234.5, 1,234.5, 1 234,5 or 1.2k
     #[arg(required_unless_present("list"))]
-    pub value: Option<f64>,
+    pub value: Option<String>,
     /// The unit to convert from
     #[arg(required_unless_present("list"))]
     pub from_unit: Option<String>,
-    /// The unit to convert to
-    #[arg(required_unless_present("list"))]
+    /// The unit to convert to, several separated by commas, or `all`
+    #[arg(required_unless_present_any(["list", "targets"]))]
     pub to_unit: Option<String>,
+    /// Units to convert to, separated by commas, or `all` for every unit of the category
+    #[arg(long = "to", value_name = "UNITS", conflicts_with = "to_unit")]
+    pub targets: Option<String>,
+    /// Convert temperatures as differences, so that a change of 10 c is one of 18 f
+    #[arg(long)]
+    pub delta: bool,
+    /// Use the exchange rates of a past day, or of the closest day there are rates for
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub date: Option<NaiveDate>,
     /// List all units of this measurement type
     #[arg(long, short='L', global=true)]
     pub list: bool
 }
 
+#[derive(Debug, Clone, Args)]
+pub struct CsvArgs {
+    /// The CSV file to read, `-` for stdin
+    pub input: String,
+    /// Name of the column holding the values
+    #[arg(long)]
+    pub column: String,
+    /// The unit of every value in the column
+    #[arg(long, required_unless_present("unit_column"))]
+    pub from: Option<String>,
+    /// Name of the column holding the unit of each row's value
+    #[arg(long, conflicts_with("from"))]
+    pub unit_column: Option<String>,
+    /// The unit to convert to
+    #[arg(long)]
+    pub to: String,
+    /// Append the results as a new column with this name instead of rewriting the values
+    #[arg(long, value_name = "NAME")]
+    pub append: Option<String>,
+    /// Field delimiter
+    #[arg(long, default_value_t = ',')]
+    pub delimiter: char,
+}
+
+#[derive(Debug)]
+pub struct Conversion {
+    pub category: &'static str,
+    pub value: Number,
+    /// The unit as it was given on the command line
+    pub from_unit: String,
+    /// The canonical name of the unit converted from
+    pub from_name: String,
+    pub result: f64,
+    pub to_unit: String,
+    pub to_name: String,
+    /// Date of the exchange rates used, for currency conversions
+    pub date: Option<DateTime<Utc>>,
+    /// The expression that was evaluated, for expression mode
+    pub expression: Option<String>,
+    /// The result split over several units, for mixed targets such as `ft+in`, with a part for
+    /// each unit even when zero, see [`Quantity::round_mixed`]
+    pub parts: Vec<Quantity>,
+    /// The result as an exact fraction, when both units are defined exactly
+    pub exact: Option<BigRational>,
+    /// What to tell the user besides the result, such as that the exchange rates are stale
+    pub warning: Option<String>,
+}
+
+/// What a successful invocation produced, left to the caller to print
+#[derive(Debug)]
+pub enum Outcome {
+    Conversion(Conversion),
+    UnitList(String),
+    /// One value converted to several units, sorted by the magnitude of the results
+    Table(Vec<Conversion>),
+    /// The interactive session was asked for, see [`repl::run`]
+    Repl,
+    /// Lines of `input` (a file or `-` for stdin) are to be converted, see [`batch::run`]
+    Batch { input: String, units: Vec<String> },
+    /// A column of a CSV file is to be converted, see [`csv::run`]
+    Csv(CsvArgs),
+}
+
+impl Outcome {
+    /// What to tell the user besides the result, see [`Conversion::warning`]. The conversions of
+    /// a table share their exchange rates, so the first one speaks for all.
+    pub fn warning(&self) -> Option<&str> {
+        match self {
+            Outcome::Conversion(conversion) => conversion.warning.as_deref(),
+            Outcome::Table(conversions) => conversions.first().and_then(|conversion| conversion.warning.as_deref()),
+            Outcome::UnitList(_) | Outcome::Repl | Outcome::Batch { .. } | Outcome::Csv(_) => None,
+        }
+    }
+}
+
+impl Conversion {
+    /// The conversion, or an error if its result is out of the range of `f64`
+    pub(crate) fn finite(self) -> Result<Self, Error> {
+        if self.result.is_finite() {
+            return Ok(self);
+        }
+        let value = match &self.expression {
+            Some(expression) => expression.clone(),
+            None => format!("{} {}", self.value, self.from_unit),
+        };
+        Err(Error::OutOfRange { value, to: self.to_unit })
+    }
+}
+
+impl Fields {
+    /// The target units, given positionally or with `--to`
+    pub fn targets(&self) -> Option<&str> {
+        self.targets.as_deref().or(self.to_unit.as_deref())
+    }
+}
+
+impl Measurement {
+    pub fn category(&self) -> &'static str {
+        match self {
+            Measurement::Dist(_) => "distance",
+            Measurement::Weight(_) => "weight",
+            Measurement::Temp(_) => "temperature",
+            Measurement::DataStorage(_) => "data storage",
+            Measurement::DataTransfer(_) => "data transfer",
+            Measurement::Time(_) => "time",
+            Measurement::Volume(_) => "volume",
+            Measurement::Area(_) => "area",
+            Measurement::Frequency(_) => "frequency",
+            Measurement::Force(_) => "force",
+            Measurement::Energy(_) => "energy",
+            Measurement::Power(_) => "power",
+            Measurement::Speed(_) => "speed",
+            Measurement::Currency(_) => "currency",
+            Measurement::Repl => "repl",
+            Measurement::Csv(_) => "csv",
+        }
+    }
+
+    /// The conversion arguments, for every subcommand but `repl` and `csv`
+    pub fn fields(&self) -> Option<&Fields> {
+        match self {
+            Measurement::Dist(fields)
+            | Measurement::Weight(fields)
+            | Measurement::Temp(fields)
+            | Measurement::DataStorage(fields)
+            | Measurement::DataTransfer(fields)
+            | Measurement::Time(fields)
+            | Measurement::Volume(fields)
+            | Measurement::Area(fields)
+            | Measurement::Frequency(fields)
+            | Measurement::Force(fields)
+            | Measurement::Energy(fields)
+            | Measurement::Power(fields)
+            | Measurement::Speed(fields)
+            | Measurement::Currency(fields) => Some(fields),
+            Measurement::Repl | Measurement::Csv(_) => None,
+        }
+    }
+}
+
 impl Cmd {
-    pub fn execute(&self) -> Result<(f64, &str, f64, &str, Option<String>), &'static str> {
-        match &self.measurement {
-            Measurement::Dist(fields) => handle_conversion(fields, distance::convert, distance::help_text),
-            Measurement::Weight(fields) => handle_conversion(fields, weight::convert, weight::help_text),
-            Measurement::Temp(fields) => handle_conversion(fields, temperature::convert, temperature::help_text),
-            Measurement::DataStorage(fields) => handle_conversion(fields, data_storage::convert, data_storage::help_text),
-            Measurement::DataTransfer(fields) => handle_conversion(fields, data_transfer::convert, data_transfer::help_text),
-            Measurement::Time(fields) => handle_conversion(fields, time::convert, time::help_text),
-            Measurement::Volume(fields) => handle_conversion(fields, volume::convert, volume::help_text),
-            Measurement::Area(fields) => handle_conversion(fields, area::convert, area::help_text),
-            Measurement::Frequency(fields) => handle_conversion(fields, frequency::convert, frequency::help_text),
-            Measurement::Force(fields) => handle_conversion(fields, force::convert, force::help_text),
-            Measurement::Energy(fields) => handle_conversion(fields, energy::convert, energy::help_text),
-            Measurement::Power(fields) => handle_conversion(fields, power::convert, power::help_text),
-            Measurement::Speed(fields) => handle_conversion(fields, speed::convert, speed::help_text),
-            Measurement::Currency(fields) => handle_currency_conversion(fields, currency::convert, currency::help_text),
-        }
-    }
-}
-
-fn handle_conversion(
-    fields: &Fields, 
-    convert: fn(f64, &str, &str) -> Result<f64, &'static str>,
-    help_text: fn() -> String
-) -> Result<(f64, &str, f64, &str, Option<String>), &'static str> {
+    /// Parses the command line, also when global options come before the subcommand
+    pub fn parse_args() -> Self {
+        Self::try_parse_args(std::env::args_os()).unwrap_or_else(|e| e.exit())
+    }
+
+    /// Parses the arguments, reading `cnv --format json dist 10 km mi` as `cnv dist --format json
+    /// 10 km mi`. Clap would take a subcommand written after options for the expression, so the
+    /// first word that isn't an option or an option's value is moved first when it names one.
+    pub fn try_parse_args<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let mut args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+        if let Some(position) = leading_subcommand(&args) {
+            let subcommand = args.remove(position);
+            args.insert(1, subcommand);
+        }
+        Self::try_parse_from(args)
+    }
+
+    /// How exchange rates are loaded, see [`currency::set_policy`]
+    pub fn rate_policy(&self) -> currency::RatePolicy {
+        currency::RatePolicy { offline: self.offline, max_age: chrono::TimeDelta::hours(self.max_age.into()) }
+    }
+
+    /// Runs the command, failing on results too large for `f64` rather than printing `inf`
+    pub fn execute(&self) -> Result<Outcome, Error> {
+        match self.outcome()? {
+            Outcome::Conversion(conversion) => conversion.finite().map(Outcome::Conversion),
+            Outcome::Table(conversions) => {
+                conversions.into_iter().map(Conversion::finite).collect::<Result<_, _>>().map(Outcome::Table)
+            }
+            outcome => Ok(outcome),
+        }
+    }
+
+    fn outcome(&self) -> Result<Outcome, Error> {
+        let Some(measurement) = &self.measurement else {
+            return self.detect_and_convert();
+        };
+
+        let fields = match measurement {
+            Measurement::Repl => return Ok(Outcome::Repl),
+            // The output of `cnv csv` is the file it reads, so it has no other format
+            Measurement::Csv(_) if self.format != Format::Text => {
+                return Err(Error::UnusedOption { option: "--format", command: "csv" })
+            }
+            Measurement::Csv(args) => return Ok(Outcome::Csv(args.clone())),
+            measurement => measurement.fields().expect("every other measurement has fields"),
+        };
+        let locale = self.style.locale();
+        match measurement.category() {
+            "currency" => handle_currency_conversion(fields, locale),
+            category => handle_conversion(fields, category, locale),
+        }
+    }
+
+    fn detect_and_convert(&self) -> Result<Outcome, Error> {
+        if let Some(input) = &self.batch {
+            return Ok(Outcome::Batch { input: input.clone(), units: self.args.clone() });
+        }
+        // A lone `-` reads the lines to convert from stdin
+        if let Some(("-", units)) = self.args.split_first().map(|(first, rest)| (first.as_str(), rest)) {
+            return Ok(Outcome::Batch { input: "-".to_string(), units: units.to_vec() });
+        }
+
+        let locale = self.style.locale();
+        let args: Vec<&str> = self.args.iter().map(String::as_str).collect();
+        if let Some(targets) = &self.targets {
+            if let [value, from] = args.as_slice() {
+                if let Ok(value) = locale.parse_number(value) {
+                    return convert_many(None, &value, from, targets).map(Outcome::Table);
+                }
+            }
+            if args.is_empty() {
+                return Err(Error::MissingArgument("Value"));
+            }
+            let evaluation = evaluate(&self.args.join(" "))?;
+            return convert_many(None, &evaluation.value.into(), &evaluation.unit, targets).map(Outcome::Table);
+        }
+
+        if let [value, from, to] | [value, from, "to", to] = args.as_slice() {
+            if let Ok(value) = locale.parse_number(value) {
+                if is_many(to) {
+                    return convert_many(None, &value, from, to).map(Outcome::Table);
+                }
+                return convert_units(&value, from, to).map(Outcome::Conversion);
+            }
+        }
+        if args.is_empty() {
+            return Err(Error::MissingArgument("Value"));
+        }
+        // A mixed quantity may come with its target alone, as in `cnv 5ft10in cm`
+        let (expression, target) = match args.as_slice() {
+            [expression @ .., "to", target] => (expression.join(" "), Some(*target)),
+            [expression, target] if locale.parse_number(expression).is_err() => (expression.to_string(), Some(*target)),
+            _ => (self.args.join(" "), None),
+        };
+        match target {
+            Some(target) if target == "auto" || target.contains('+') => {
+                let evaluation = evaluate(&expression)?;
+                let conversion = convert_units(&evaluation.value.into(), &evaluation.unit, target)?;
+                Ok(Outcome::Conversion(Conversion { expression: Some(evaluation.expression), ..conversion }))
+            }
+            Some(target) => evaluate_expression(&format!("{} to {}", expression, target)),
+            None => evaluate_expression(&expression),
+        }
+    }
+}
+
+/// Where the subcommand is when options come before it
+fn leading_subcommand(args: &[OsString]) -> Option<usize> {
+    let command = Cmd::command();
+    let mut i = 1;
+    while let Some(arg) = args.get(i)?.to_str() {
+        let option = if let Some(long) = arg.strip_prefix("--").filter(|long| !long.is_empty()) {
+            command.get_arguments().find(|option| option.get_long() == Some(long))
+        } else if let Some(short) = arg.strip_prefix('-').and_then(|short| short.parse::<char>().ok()) {
+            command.get_arguments().find(|option| option.get_short() == Some(short))
+        } else {
+            return (i > 1 && command.find_subcommand(arg).is_some()).then_some(i);
+        };
+        // The value of `--format json` is the next argument, that of `--format=json` isn't
+        let separate_value = option.is_some_and(|option| option.get_action().takes_values());
+        i += if separate_value { 2 } else { 1 };
+    }
+    None
+}
+
+pub(crate) fn convert_units(value: &Number, from: &str, to: &str) -> Result<Conversion, Error> {
+    let registry = registry();
+    if to == "auto" {
+        let category = category_of(from, to)?.ok_or_else(|| Error::unknown_unit("", from, Vec::new()))?;
+        return convert_in(category, value, from, to);
+    }
+    // Mixed targets such as `ft+in` are detected from their first unit
+    let first = to.split('+').next().unwrap_or(to);
+    if registry.find(from).is_none() || registry.find(first).is_none() {
+        return convert_compound(value, from, to);
+    }
+
+    let dimension = registry.detect(from, first)?;
+    convert_in(dimension.name(), value, from, to)
+}
+
+fn evaluate_expression(input: &str) -> Result<Outcome, Error> {
+    let evaluation = evaluate(input)?;
+
+    Ok(Outcome::Conversion(Conversion {
+        category: evaluation.category().unwrap_or("expression"),
+        value: evaluation.value.into(),
+        from_unit: evaluation.unit.clone(),
+        from_name: evaluation.unit.clone(),
+        result: evaluation.value,
+        to_unit: evaluation.unit.clone(),
+        to_name: evaluation.unit.clone(),
+        date: None,
+        expression: Some(evaluation.expression),
+        parts: Vec::new(),
+        exact: None,
+        warning: None,
+    }))
+}
+
+fn handle_conversion(fields: &Fields, category: &'static str, locale: Locale) -> Result<Outcome, Error> {
+    let registry = registry();
 
     if fields.list {
-        println!("{}", help_text());
-        process::exit(0);
+        let dimension = registry.dimension(category).expect("every measurement is registered");
+        return Ok(Outcome::UnitList(dimension.help_text()));
+    }
+
+    let value = &locale.parse_number(fields.value.as_deref().ok_or(Error::MissingArgument("Value"))?)?;
+    let from = fields.from_unit.as_deref().ok_or(Error::MissingArgument("From unit"))?;
+    let to = fields.targets().ok_or(Error::MissingArgument("To unit"))?;
+    let (from, to) = if fields.delta {
+        let targets: Vec<String> = to.split(',').map(|to| as_difference(category, to)).collect();
+        (as_difference(category, from), targets.join(","))
+    } else {
+        (from.to_string(), to.to_string())
+    };
+
+    if is_many(&to) {
+        return convert_many(Some(category), value, &from, &to).map(Outcome::Table);
+    }
+    convert_in(category, value, &from, &to).map(Outcome::Conversion)
+}
+
+/// The temperature difference unit for a unit such as `f`, i.e. `Δf`, or the unit as given if
+/// there is none
+fn as_difference(category: &str, alias: &str) -> String {
+    let registry = registry();
+    alias
+        .split('+')
+        .map(|alias| {
+            registry
+                .find_in(category, alias.trim())
+                .ok()
+                .map(|unit| format!("Δ{}", unit.symbol()))
+                .filter(|difference| registry.find_in(category, difference).is_ok())
+                .unwrap_or_else(|| alias.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+fn convert_in(category: &'static str, value: &Number, from: &str, to: &str) -> Result<Conversion, Error> {
+    let registry = registry();
+    let from_unit = registry.find_in(category, from)?;
+    let quantity = Quantity::new(value.to_f64(), from_unit);
+
+    if to.contains('+') {
+        let units = to
+            .split('+')
+            .map(|alias| registry.find_in(category, alias.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let parts = quantity.split_mixed(&units)?;
+        return Ok(Conversion {
+            category,
+            value: value.clone(),
+            from_unit: from.to_string(),
+            from_name: from_unit.name.to_string(),
+            result: quantity.to(parts[0].unit)?.value,
+            to_unit: to.to_string(),
+            to_name: units.iter().map(|unit| unit.name).collect::<Vec<_>>().join("+"),
+            date: None,
+            expression: None,
+            parts,
+            exact: None,
+            warning: None,
+        });
     }
 
-    let value = fields.value.ok_or("Value required when not listing units")?;
-    let from = fields.from_unit.as_deref().ok_or("From unit required")?;
-    let to = fields.to_unit.as_deref().ok_or("To unit required")?;
+    let result = match to {
+        "auto" => quantity.humanize()?,
+        to => quantity.to(registry.find_in(category, to)?)?,
+    };
+    // The unit `auto` picked is shown instead of "auto"
+    let to = if to == "auto" { result.unit.symbol() } else { to };
+
+    Ok(Conversion {
+        category,
+        value: value.clone(),
+        from_unit: from.to_string(),
+        from_name: from_unit.name.to_string(),
+        result: result.value,
+        to_unit: to.to_string(),
+        to_name: result.unit.name.to_string(),
+        date: None,
+        expression: None,
+        parts: Vec::new(),
+        exact: value.exact().and_then(|value| from_unit.convert(value.clone(), &result.unit)),
+        warning: None,
+    })
+}
+
+fn convert_compound(value: &Number, from: &str, to: &str) -> Result<Conversion, Error> {
+    let from_unit = CompoundUnit::parse(from)?;
+    let to_unit = CompoundUnit::parse(to)?;
+    let result = from_unit.convert(value.to_f64(), &to_unit)?;
 
-    let result = convert(value, from, to)?;
-    Ok((value, from, result, to, None))
+    Ok(Conversion {
+        category: "compound",
+        value: value.clone(),
+        from_unit: from.to_string(),
+        from_name: from_unit.expression,
+        result,
+        to_unit: to.to_string(),
+        to_name: to_unit.expression,
+        date: None,
+        expression: None,
+        parts: Vec::new(),
+        exact: None,
+        warning: None,
+    })
 }
 
-fn handle_currency_conversion(
-    fields: &Fields,
-    convert: fn(f64, &str, &str) -> Result<(f64, String), &'static str>,
-    help_text: fn() -> String
-) -> Result<(f64, &str, f64, &str, Option<String>), &'static str> {
+fn handle_currency_conversion(fields: &Fields, locale: Locale) -> Result<Outcome, Error> {
     if fields.list {
-        println!("{}", help_text());
-        process::exit(0);
+        // A value given with --list searches the list, as in `cnv currency --list dollar`
+        return Ok(Outcome::UnitList(currency::help_text(fields.value.as_deref())?));
     }
 
-    let value = fields.value.ok_or("Value required")?;
-    let from = fields.from_unit.as_deref().ok_or("From unit required")?;
-    let to = fields.to_unit.as_deref().ok_or("To unit required")?;
+    let value = &locale.parse_number(fields.value.as_deref().ok_or(Error::MissingArgument("Value"))?)?;
+    let from = fields.from_unit.as_deref().ok_or(Error::MissingArgument("From unit"))?;
+    let to = fields.targets().ok_or(Error::MissingArgument("To unit"))?;
+
+    let dated;
+    let rates = match fields.date {
+        Some(date) => {
+            dated = currency::on(date)?;
+            &dated
+        }
+        None => currency::latest()?,
+    };
+    if is_many(to) {
+        return convert_currencies(value, from, to, rates).map(Outcome::Table);
+    }
+    convert_currency(value, from, to, rates).map(Outcome::Conversion)
+}
 
-    let (result, date) = convert(value, from, to)?;
-    Ok((value, from, result, to, Some(date)))
-}
\ No newline at end of file
+fn convert_currency(value: &Number, from: &str, to: &str, rates: &currency::Rates) -> Result<Conversion, Error> {
+    Ok(Conversion {
+        category: "currency",
+        value: value.clone(),
+        from_unit: from.to_string(),
+        from_name: rates.resolve(from)?,
+        result: rates.convert(value.to_f64(), from, to)?,
+        to_unit: to.to_string(),
+        to_name: rates.resolve(to)?,
+        date: Some(rates.date),
+        expression: None,
+        parts: Vec::new(),
+        exact: None,
+        warning: rates.warning(),
+    })
+}
+
+/// Converts to every currency of a comma separated list, or to every currency for `all`
+fn convert_currencies(value: &Number, from: &str, targets: &str, rates: &currency::Rates) -> Result<Vec<Conversion>, Error> {
+    let targets = match targets {
+        "all" => rates.codes(),
+        targets => targets.split(',').map(str::trim).filter(|to| !to.is_empty()).map(str::to_string).collect(),
+    };
+    let mut conversions =
+        targets.iter().map(|to| convert_currency(value, from, to, rates)).collect::<Result<Vec<_>, _>>()?;
+    conversions.sort_by(|a, b| a.result.abs().total_cmp(&b.result.abs()));
+    Ok(conversions)
+}
+
+/// Whether `to` names several units: a comma separated list or `all`
+fn is_many(to: &str) -> bool {
+    to == "all" || to.contains(',')
+}
+
+/// Converts to every unit of a comma separated list, or to every unit of the category for `all`.
+///
+/// Without a category it is detected from the units, as for a single target.
+fn convert_many(category: Option<&'static str>, value: &Number, from: &str, targets: &str) -> Result<Vec<Conversion>, Error> {
+    if targets == "all" {
+        return convert_to_all(category, value, from);
+    }
+
+    let mut conversions = targets
+        .split(',')
+        .map(str::trim)
+        .filter(|to| !to.is_empty())
+        .map(|to| match category {
+            Some(category) => convert_in(category, value, from, to),
+            None => convert_units(value, from, to),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    conversions.sort_by(|a, b| a.result.abs().total_cmp(&b.result.abs()));
+    Ok(conversions)
+}
+
+fn convert_to_all(category: Option<&'static str>, value: &Number, from: &str) -> Result<Vec<Conversion>, Error> {
+    let registry = registry();
+
+    let category = match category {
+        Some(category) => category,
+        None => match category_of(from, "all")? {
+            Some(category) => category,
+            None => return convert_compound_to_all(value, from),
+        },
+    };
+
+    let from_unit = registry.find_in(category, from)?;
+    let quantities = Quantity::new(value.to_f64(), from_unit).to_many(registry.units_of(category).copied())?;
+    Ok(quantities
+        .into_iter()
+        .map(|quantity| Conversion {
+            category,
+            value: value.clone(),
+            from_unit: from.to_string(),
+            from_name: from_unit.name.to_string(),
+            result: quantity.value,
+            to_unit: quantity.unit.symbol().to_string(),
+            to_name: quantity.unit.name.to_string(),
+            date: None,
+            expression: None,
+            parts: Vec::new(),
+            exact: value.exact().and_then(|value| from_unit.convert(value.clone(), &quantity.unit)),
+            warning: None,
+        })
+        .collect())
+}
+
+/// The one category `from` is a unit of, if any, for targets like `all` that name no unit
+fn category_of(from: &str, to: &str) -> Result<Option<&'static str>, Error> {
+    let registry = registry();
+    let candidates: Vec<_> = registry
+        .dimensions()
+        .filter(|dimension| registry.find_in(dimension.name(), from).is_ok())
+        .collect();
+
+    match candidates.as_slice() {
+        [dimension] => Ok(Some(dimension.name())),
+        [] => Ok(None),
+        _ => Err(Error::AmbiguousUnits {
+            from: from.to_string(),
+            to: to.to_string(),
+            candidates: candidates.iter().map(|dimension| dimension.command()).collect(),
+        }),
+    }
+}
+
+/// Converts a compound unit to every unit of the category with the same dimensions
+fn convert_compound_to_all(value: &Number, from: &str) -> Result<Vec<Conversion>, Error> {
+    let from_unit = CompoundUnit::parse(from)?;
+    let dimension = registry()
+        .dimensions()
+        .find(|dimension| dimension.base_dimensions() == from_unit.dimensions)
+        .ok_or(Error::MissingArgument("A category to list the units of"))?;
+
+    let mut conversions: Vec<_> = registry()
+        .units_of(dimension.name())
+        .map(|unit| Conversion {
+            category: dimension.name(),
+            value: value.clone(),
+            from_unit: from.to_string(),
+            from_name: from_unit.expression.clone(),
+            result: value.to_f64() * from_unit.factor / unit.si_factor(),
+            to_unit: unit.symbol().to_string(),
+            to_name: unit.name.to_string(),
+            date: None,
+            expression: None,
+            parts: Vec::new(),
+            exact: None,
+            warning: None,
+        })
+        .collect();
+    conversions.sort_by(|a, b| a.result.abs().total_cmp(&b.result.abs()));
+    Ok(conversions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Cmd {
+        Cmd::try_parse_args(line.split_whitespace()).unwrap_or_else(|e| panic!("{}: {}", line, e))
+    }
+
+    #[test]
+    fn reads_options_before_the_subcommand() {
+        let cmd = parse("cnv --format json dist 10 km mi");
+        assert_eq!(cmd.format, Format::Json);
+        assert!(matches!(cmd.measurement, Some(Measurement::Dist(_))));
+
+        let cmd = parse("cnv --precision 2 dist 1 mi km");
+        assert_eq!(cmd.style.precision, Some(2));
+        let fields = cmd.measurement.as_ref().and_then(Measurement::fields).unwrap();
+        assert_eq!(fields.value.as_deref(), Some("1"));
+
+        let cmd = parse("cnv --offline --format=csv currency 1 usd eur");
+        assert!(cmd.offline);
+        assert_eq!(cmd.format, Format::Csv);
+        assert!(matches!(cmd.measurement, Some(Measurement::Currency(_))));
+    }
+
+    #[test]
+    fn keeps_expressions_without_a_subcommand() {
+        let cmd = parse("cnv --format json 10 km mi");
+        assert!(cmd.measurement.is_none());
+        assert_eq!(cmd.args, ["10", "km", "mi"]);
+
+        let cmd = parse("cnv --precision 1 -5 c f");
+        assert!(cmd.measurement.is_none());
+        assert_eq!(cmd.args, ["-5", "c", "f"]);
+    }
+
+    #[test]
+    fn reads_m_as_metres_outside_mixed_durations() {
+        match parse("cnv 5 m --to all").execute() {
+            Ok(Outcome::Table(conversions)) => assert!(conversions.iter().all(|c| c.category == "distance")),
+            other => panic!("expected a table of distances, got {:?}", other),
+        }
+        match parse("cnv 5 m auto").execute() {
+            Ok(Outcome::Conversion(conversion)) => assert_eq!(conversion.category, "distance"),
+            other => panic!("expected a distance, got {:?}", other),
+        }
+        match parse("cnv 1h30m min").execute() {
+            Ok(Outcome::Conversion(conversion)) => assert_eq!(conversion.result, 90.0),
+            other => panic!("expected 90 min, got {:?}", other),
+        }
+    }
+}
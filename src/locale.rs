@@ -0,0 +1,210 @@
+use std::str::FromStr;
+
+use crate::error::Error;
+use crate::number::Number;
+
+/// Characters that only ever group digits, as in `1 234,5`, `1_000` or `1'234.5`
+const GROUP_MARKS: [char; 5] = [' ', '_', '\'', '\u{a0}', '\u{202f}'];
+
+/// Multipliers a value may end with, as in `1.2e3k`
+const SUFFIXES: [(char, &str); 6] = [('k', "e3"), ('M', "e6"), ('G', "e9"), ('T', "e12"), ('P', "e15"), ('E', "e18")];
+
+/// How numbers are written: the decimal separator and the separator between groups of digits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    decimal: char,
+    group: char,
+}
+
+impl Default for Locale {
+    /// A decimal point, and commas grouping digits when grouping is asked for
+    fn default() -> Self {
+        Locale { decimal: '.', group: ',' }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = Error;
+
+    /// Reads a language tag such as `en`, `de-DE`, `fr_FR` or `de-CH`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tag = s.to_lowercase().replace('_', "-");
+        let (language, region) = tag.split_once('-').unwrap_or((&tag, ""));
+        let (decimal, group) = match (language, region.split('.').next().unwrap_or_default()) {
+            ("c" | "posix" | "en" | "ja" | "zh" | "ko" | "he" | "th", _) => ('.', ','),
+            ("de" | "it", "ch") | ("rm", _) => ('.', '\''),
+            ("de" | "nl" | "it" | "es" | "pt" | "da" | "id" | "tr" | "el" | "ro" | "sl" | "hr" | "sr", _) => (',', '.'),
+            ("fr" | "ru" | "pl" | "cs" | "sk" | "uk" | "sv" | "nb" | "no" | "fi" | "hu" | "bg" | "lt" | "lv" | "et", _) => {
+                (',', '\u{202f}')
+            }
+            _ => return Err(Error::UnknownLocale(s.to_string())),
+        };
+        Ok(Locale { decimal, group })
+    }
+}
+
+impl Locale {
+    /// Reads a value written with grouped digits or a decimal comma, such as `1,234.5`,
+    /// `1 234,5`, `1_000` or `1.2e3k`.
+    ///
+    /// Separators are told apart by the way they are used where possible: the last of `.` and
+    /// `,` is the decimal separator when both appear, and one that appears several times groups
+    /// digits. Only a lone separator, as in `1,234`, is read the way the locale writes it.
+    pub fn parse_number(&self, text: &str) -> Result<Number, Error> {
+        let invalid = || Error::ParseValue(text.to_string());
+        let trimmed = text.trim();
+
+        let (mantissa, multiplier) = match SUFFIXES.iter().find(|(suffix, _)| trimmed.ends_with(*suffix)) {
+            Some((suffix, exponent)) => (&trimmed[..trimmed.len() - suffix.len_utf8()], Some(*exponent)),
+            None => (trimmed, None),
+        };
+        let (mantissa, exponent) = match mantissa.find(['e', 'E']) {
+            Some(index) => mantissa.split_at(index),
+            None => (mantissa, ""),
+        };
+        let mantissa: String = mantissa.chars().filter(|c| !GROUP_MARKS.contains(c)).collect();
+
+        let last_point = mantissa.rfind('.');
+        let last_comma = mantissa.rfind(',');
+        let decimal = match (last_point, last_comma) {
+            (Some(point), Some(comma)) => Some(if point > comma { '.' } else { ',' }),
+            (Some(_), None) if mantissa.matches('.').count() > 1 => None,
+            (None, Some(_)) if mantissa.matches(',').count() > 1 => None,
+            (Some(_), None) => self.lone_separator(&mantissa, '.'),
+            (None, Some(_)) => self.lone_separator(&mantissa, ','),
+            (None, None) => None,
+        };
+
+        let mut normalized: String = mantissa
+            .chars()
+            .filter(|&c| c != '.' && c != ',' || Some(c) == decimal)
+            .map(|c| if Some(c) == decimal { '.' } else { c })
+            .collect();
+        // Both the exponent and the suffix scale the value, so `1.2e3k` is 1.2e6
+        let mut power: i32 = 0;
+        for exponent in [exponent, multiplier.unwrap_or_default()] {
+            if let Some(digits) = exponent.strip_prefix(['e', 'E']) {
+                let digits = digits.strip_prefix('+').unwrap_or(digits).parse::<i32>().map_err(|_| invalid())?;
+                power = power.checked_add(digits).ok_or_else(invalid)?;
+            }
+        }
+        if !exponent.is_empty() || multiplier.is_some() {
+            normalized.push_str(&format!("e{}", power));
+        }
+
+        // Only decimals are read, so that words such as `inf` are left to expressions
+        if !normalized.trim_start_matches(['-', '+']).starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+            return Err(invalid());
+        }
+        normalized.parse().map_err(|_| invalid())
+    }
+
+    /// The separator if it is the decimal separator when it appears once, or `None` if it groups
+    /// thousands as `,` does in `1,234` for English
+    fn lone_separator(&self, mantissa: &str, separator: char) -> Option<char> {
+        let digits_after = mantissa.rsplit(separator).next().map_or(0, str::len);
+        (separator != self.group || digits_after != 3).then_some(separator)
+    }
+
+    /// Writes a number produced by the formatter, e.g. `-1234.5` or `1.5e-9`, with the locale's
+    /// decimal separator and, when `grouping`, its digits grouped by thousands
+    pub fn format(&self, number: &str, grouping: bool) -> String {
+        if *self == Locale::default() && !grouping {
+            return number.to_string();
+        }
+
+        let (mantissa, exponent) = match number.find(['e', 'E']) {
+            Some(index) => number.split_at(index),
+            None => (number, ""),
+        };
+        let (sign, digits) = match mantissa.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", mantissa),
+        };
+        let (whole, fraction) = match digits.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (digits, None),
+        };
+        // Fractions such as -155/9 and words such as inf are left alone
+        if !whole.bytes().all(|b| b.is_ascii_digit()) || fraction.is_some_and(|f| !f.bytes().all(|b| b.is_ascii_digit())) {
+            return number.to_string();
+        }
+
+        let mut output = String::from(sign);
+        for (i, digit) in whole.chars().enumerate() {
+            if grouping && i > 0 && (whole.len() - i) % 3 == 0 {
+                output.push(self.group);
+            }
+            output.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            output.push(self.decimal);
+            output.push_str(fraction);
+        }
+        output.push_str(exponent);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(locale: &str, text: &str) -> f64 {
+        locale.parse::<Locale>().unwrap().parse_number(text).unwrap().to_f64()
+    }
+
+    #[test]
+    fn reads_group_separators() {
+        assert_eq!(parse("en", "1234.5"), 1234.5);
+        assert_eq!(parse("en", "1,234.5"), 1234.5);
+        assert_eq!(parse("en", "1 234,5"), 1234.5);
+        assert_eq!(parse("en", "1_000"), 1000.0);
+    }
+
+    #[test]
+    fn reads_exponents_and_multipliers() {
+        assert_eq!(parse("en", "1.2k"), 1200.0);
+        assert_eq!(parse("en", "1.2e3k"), 1.2e6);
+    }
+
+    #[test]
+    fn reads_lone_separators_the_locale_way() {
+        assert_eq!(parse("en", "1,234"), 1234.0);
+        assert_eq!(parse("de", "1,234"), 1.234);
+        assert_eq!(parse("de", "1.234,5"), 1234.5);
+        assert_eq!(parse("de", "1.234.567"), 1234567.0);
+        assert_eq!(parse("fr", "1,5"), 1.5);
+        assert_eq!(parse("de-CH", "1'234.5"), 1234.5);
+    }
+
+    #[test]
+    fn rejects_words() {
+        let locale = Locale::default();
+        assert!(matches!(locale.parse_number("inf"), Err(Error::ParseValue(_))));
+        assert!(matches!(locale.parse_number("abc"), Err(Error::ParseValue(_))));
+    }
+
+    #[test]
+    fn rejects_overflowing_exponents() {
+        let locale = Locale::default();
+        assert!(matches!(locale.parse_number("1e2147483647k"), Err(Error::ParseValue(_))));
+        assert!(matches!(locale.parse_number("1e999999999"), Err(Error::ParseValue(_))));
+    }
+
+    #[test]
+    fn reads_language_tags() {
+        assert_eq!("de_DE".parse::<Locale>().unwrap(), "de".parse().unwrap());
+        assert!(matches!("xx".parse::<Locale>(), Err(Error::UnknownLocale(_))));
+    }
+
+    #[test]
+    fn writes_numbers() {
+        let de: Locale = "de".parse().unwrap();
+        assert_eq!(de.format("-1234567.5", true), "-1.234.567,5");
+        assert_eq!(de.format("1.5e-9", false), "1,5e-9");
+        assert_eq!(Locale::default().format("1234567", true), "1,234,567");
+        assert_eq!(de.format("-155/9", true), "-155/9");
+        assert_eq!("fr".parse::<Locale>().unwrap().format("1234.5", true), "1\u{202f}234,5");
+    }
+}
@@ -1,25 +1,55 @@
-use clap::Parser;
+use std::process;
 
-use cnv::Cmd;
+use cnv::conversions::currency;
+use cnv::{batch, csv, output, repl, Cmd, Outcome};
 
 fn main() {
-    let cmd = Cmd::parse();
+    let cmd = Cmd::parse_args();
+    currency::set_policy(cmd.rate_policy());
 
-    match cmd.execute() {
-        Ok((value, from_unit, result, to_unit, date_opt)) => {
-            let mut output = String::new();
-
-            let conversion_line = format!("{} {} = {} {}", value, from_unit, result, to_unit);
-            let dashes = "-".repeat(conversion_line.len() + 1);
-
-            output.push_str(&format!("{}\n{}\n{}", dashes, conversion_line, dashes));
-
-            if let Some(date) = date_opt {
-                output.push_str(&format!("\nas of: {}", date));
+    let outcome = cmd.execute();
+    if let Some(warning) = outcome.as_ref().ok().and_then(Outcome::warning) {
+        eprintln!("Warning: {}", warning);
+    }
+    match outcome {
+        Ok(Outcome::Conversion(conversion)) => {
+            if let Some(header) = cmd.format.header() {
+                println!("{}", header);
             }
-
-            println!("{}", output);
+            println!("{}", output::render(&conversion, cmd.format, &cmd.style));
+        }
+        Ok(Outcome::Table(conversions)) => {
+            if let Some(header) = cmd.format.header() {
+                println!("{}", header);
+            }
+            println!("{}", output::render_table(&conversions, cmd.format, &cmd.style));
+        }
+        Ok(Outcome::UnitList(list)) => println!("{}", list),
+        Ok(Outcome::Batch { input, units }) => match batch::run(&input, &units, cmd.format, &cmd.style) {
+            Ok(0) => {}
+            Ok(code) => process::exit(code),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(e.exit_code());
+            }
+        },
+        Ok(Outcome::Csv(args)) => match csv::run(&args, &cmd.style) {
+            Ok(0) => {}
+            Ok(code) => process::exit(code),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(e.exit_code());
+            }
+        },
+        Ok(Outcome::Repl) => {
+            if let Err(e) = repl::run(cmd.format, &cmd.style) {
+                eprintln!("{}", e);
+                process::exit(e.exit_code());
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(e.exit_code());
         }
-        Err(e) => println!("{}", e),
     }
 }
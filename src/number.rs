@@ -0,0 +1,189 @@
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+use std::str::FromStr;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Pow, ToPrimitive};
+
+use crate::error::Error;
+use crate::exact;
+use crate::registry::Unit;
+
+/// Largest power of ten a value is read exactly with. Beyond it the value is only held as `f64`,
+/// where it is out of range anyway, rather than computing a fraction of a billion digits.
+const MAX_EXACT_EXPONENT: u32 = 1000;
+
+/// Most digits a value is written with in full, beyond which it is written as e.g. `1e300`
+const MAX_WRITTEN_DIGITS: usize = 40;
+
+/// A value to convert, kept exact however many digits it is written with, so that byte counts
+/// beyond 2^53 or a yottabyte in bits lose nothing.
+///
+/// Values that are not decimals, such as `inf`, are only held as `f64`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Number {
+    approx: f64,
+    exact: Option<BigRational>,
+}
+
+impl Number {
+    pub fn to_f64(&self) -> f64 {
+        self.approx
+    }
+
+    /// The value as an exact fraction, if it is a decimal
+    pub fn exact(&self) -> Option<&BigRational> {
+        self.exact.as_ref()
+    }
+}
+
+impl From<f64> for Number {
+    /// Takes the value as the decimal it reads as, e.g. 0.1 as 1/10
+    fn from(value: f64) -> Self {
+        Number { approx: value, exact: exact::value(value) }
+    }
+}
+
+impl From<BigRational> for Number {
+    fn from(value: BigRational) -> Self {
+        Number { approx: value.to_f64().unwrap_or(f64::NAN), exact: Some(value) }
+    }
+}
+
+impl FromStr for Number {
+    type Err = Error;
+
+    /// Reads decimals such as `12`, `-0.5` or `1.5e30` exactly, and anything else `f64` reads.
+    /// Decimals too large for `f64`, such as `1e400`, are rejected.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let approx: f64 = s.parse().map_err(|_| Error::ParseValue(s.to_string()))?;
+        let exact = parse_decimal(s);
+        if !approx.is_finite() && s.bytes().any(|b| b.is_ascii_digit()) {
+            return Err(Error::ParseValue(s.to_string()));
+        }
+        Ok(Number { approx, exact })
+    }
+}
+
+impl fmt::Display for Number {
+    /// Writes decimals with every digit, e.g. 123456789012345678901234, unless there are too
+    /// many of them to read
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(exact) = self.exact.as_ref().filter(|exact| exact::is_terminating(exact)) {
+            let decimal = exact::to_decimal(exact, exact::decimal_places(exact));
+            if decimal.len() <= MAX_WRITTEN_DIGITS {
+                return write!(f, "{}", decimal);
+            }
+        }
+        if self.approx != 0.0 && !(1e-6..1e21).contains(&self.approx.abs()) {
+            return write!(f, "{:e}", self.approx);
+        }
+        write!(f, "{}", self.approx)
+    }
+}
+
+fn parse_decimal(s: &str) -> Option<BigRational> {
+    let (mantissa, exponent) = match s.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.strip_prefix('+').unwrap_or(exponent).parse::<i32>().ok()?),
+        None => (s, 0),
+    };
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, mantissa.strip_prefix('+').unwrap_or(mantissa)),
+    };
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if whole.is_empty() && fraction.is_empty() || !format!("{}{}", whole, fraction).bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let digits: BigInt = format!("{}{}", whole, fraction).parse().ok()?;
+    let exponent = exponent.checked_sub(fraction.len().try_into().ok()?)?;
+    if exponent.unsigned_abs() > MAX_EXACT_EXPONENT {
+        return None;
+    }
+    let power = BigRational::from_integer(BigInt::from(10).pow(exponent.unsigned_abs()));
+    let value = BigRational::from_integer(digits * sign);
+    Some(if exponent >= 0 { value * power } else { value / power })
+}
+
+/// A kind of number quantities are converted in: `f64` by default, [`BigRational`] for exact
+/// results. Conversions are written once for both, see [`Unit::convert`].
+pub trait Numeric:
+    Clone + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self>
+{
+    /// Size of the unit in its category's base unit, `None` if this kind of number can't hold it
+    fn factor(unit: &Unit) -> Option<Self>;
+    /// Reading of the unit at the base unit's zero
+    fn zero(unit: &Unit) -> Option<Self>;
+}
+
+impl Numeric for f64 {
+    fn factor(unit: &Unit) -> Option<Self> {
+        Some(unit.factor)
+    }
+
+    fn zero(unit: &Unit) -> Option<Self> {
+        Some(unit.zero)
+    }
+}
+
+impl Numeric for BigRational {
+    fn factor(unit: &Unit) -> Option<Self> {
+        unit.exact_factor()
+    }
+
+    fn zero(unit: &Unit) -> Option<Self> {
+        unit.exact_zero.map(|zero| zero.to_rational())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rational(numerator: i64, denominator: i64) -> BigRational {
+        BigRational::new(numerator.into(), denominator.into())
+    }
+
+    #[test]
+    fn reads_decimals_exactly() {
+        assert_eq!(parse_decimal("12"), Some(rational(12, 1)));
+        assert_eq!(parse_decimal("-0.5"), Some(rational(-1, 2)));
+        assert_eq!(parse_decimal("+.25"), Some(rational(1, 4)));
+        assert_eq!(parse_decimal("1.5e3"), Some(rational(1500, 1)));
+        assert_eq!(parse_decimal("15E-1"), Some(rational(3, 2)));
+        assert_eq!(parse_decimal("inf"), None);
+        assert_eq!(parse_decimal("."), None);
+    }
+
+    #[test]
+    fn keeps_big_integers_exact() {
+        let number: Number = "12345678901234567890123".parse().unwrap();
+        assert_eq!(number.to_string(), "12345678901234567890123");
+        assert_eq!(number.exact().unwrap().to_integer().to_string(), "12345678901234567890123");
+    }
+
+    #[test]
+    fn skips_the_exact_value_of_huge_exponents() {
+        assert_eq!(parse_decimal("1e999999999"), None);
+        assert_eq!(parse_decimal("1e-999999999"), None);
+        assert_eq!(parse_decimal("1e2147483647"), None);
+        let tiny: Number = "1e-999999999".parse().unwrap();
+        assert_eq!(tiny.to_f64(), 0.0);
+    }
+
+    #[test]
+    fn rejects_values_beyond_f64() {
+        assert!(matches!("1e400".parse::<Number>(), Err(Error::ParseValue(_))));
+        assert!(matches!("1e999999999".parse::<Number>(), Err(Error::ParseValue(_))));
+        assert!(matches!(format!("1{}", "0".repeat(400)).parse::<Number>(), Err(Error::ParseValue(_))));
+    }
+
+    #[test]
+    fn writes_long_values_in_scientific_notation() {
+        assert_eq!("1e300".parse::<Number>().unwrap().to_string(), "1e300");
+        assert_eq!("0.000000001".parse::<Number>().unwrap().to_string(), "0.000000001");
+        assert_eq!("-2.50".parse::<Number>().unwrap().to_string(), "-2.5");
+    }
+}
@@ -0,0 +1,449 @@
+use clap::{Args, ValueEnum};
+use num_rational::BigRational;
+use serde_json::json;
+
+use crate::conversions::currency::{iso, Currency};
+use crate::exact;
+use crate::locale::Locale;
+use crate::registry::{self, registry, Quantity};
+use crate::Conversion;
+
+const FIELDS: [&str; 6] = ["category", "value", "from", "to", "result", "date"];
+
+/// Decimal places shown when neither a precision nor significant figures are given
+const DEFAULT_PRECISION: usize = 4;
+
+/// Digits up to which an exact result is shown as a fraction such as 5/9, rather than rounded
+const MAX_FRACTION_DIGITS: usize = 6;
+
+/// Significant figures of exact results that are too long to show as a fraction
+const EXACT_SIG_FIGS: usize = 15;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum Format {
+    /// Human readable box around the conversion
+    #[default]
+    Text,
+    /// One JSON object per conversion
+    Json,
+    /// Comma separated values with a header row
+    Csv,
+    /// Tab separated values with a header row
+    Tsv,
+    /// Just the resulting number
+    Plain,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum Notation {
+    /// Fixed for everyday magnitudes, scientific for very small or large ones
+    #[default]
+    Auto,
+    /// Always fixed point, e.g. 0.000000001
+    Fixed,
+    /// Scientific notation, e.g. 1e-9
+    Sci,
+    /// Engineering notation with exponents in multiples of three, e.g. 1e-9
+    Eng,
+}
+
+/// How numbers in the result are rounded and written
+#[derive(Debug, Clone, Default, Args)]
+pub struct NumberStyle {
+    /// Number of decimal places in the result
+    #[arg(long, global=true, conflicts_with="sig_figs")]
+    pub precision: Option<usize>,
+    /// Number of significant figures in the result
+    #[arg(long, global=true, value_parser=clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub sig_figs: Option<usize>,
+    /// Notation used for the result
+    #[arg(long, value_enum, global=true, default_value_t)]
+    pub notation: Notation,
+    /// Compute with exact fractions where the units are defined exactly, printing exact
+    /// decimals or fractions such as 5/9
+    #[arg(long, global=true)]
+    pub exact: bool,
+    /// Locale numbers are written in, e.g. `de` for decimal commas; values are read in it too
+    #[arg(long, global=true, value_name="LOCALE")]
+    pub locale: Option<Locale>,
+    /// Group the digits of results by thousands, e.g. 1,234,567.5
+    #[arg(long, global=true)]
+    pub group: bool,
+}
+
+impl NumberStyle {
+    pub fn locale(&self) -> Locale {
+        self.locale.unwrap_or_default()
+    }
+
+    /// Writes a formatted number the locale's way
+    fn localize(&self, number: &str) -> String {
+        self.locale().format(number, self.group)
+    }
+}
+
+impl Format {
+    /// The header row for tabular formats, if the format has one
+    pub fn header(&self) -> Option<String> {
+        match self {
+            Format::Csv => Some(FIELDS.join(",")),
+            Format::Tsv => Some(FIELDS.join("\t")),
+            _ => None,
+        }
+    }
+}
+
+pub fn render(conversion: &Conversion, format: Format, style: &NumberStyle) -> String {
+    match format {
+        Format::Text => render_text(conversion, style),
+        Format::Json => render_json(conversion, style),
+        Format::Csv => row(conversion, style).iter().map(|field| csv_escape(field)).collect::<Vec<_>>().join(","),
+        Format::Tsv => row(conversion, style).iter().map(|field| field.replace(['\t', '\n'], " ")).collect::<Vec<_>>().join("\t"),
+        Format::Plain => format_result(conversion, style),
+    }
+}
+
+/// Renders one value converted to several units
+pub fn render_table(conversions: &[Conversion], format: Format, style: &NumberStyle) -> String {
+    match format {
+        Format::Text => render_text_table(conversions, style),
+        Format::Json => {
+            let rows: Vec<String> = conversions.iter().map(|conversion| render_json(conversion, style)).collect();
+            format!("[{}]", rows.join(","))
+        }
+        format => conversions
+            .iter()
+            .map(|conversion| render(conversion, format, style))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+pub fn format_number(value: f64, style: &NumberStyle) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+
+    let notation = match style.notation {
+        Notation::Auto if value != 0.0 && !(1e-3..1e15).contains(&value.abs()) => Notation::Sci,
+        Notation::Auto => Notation::Fixed,
+        notation => notation,
+    };
+
+    match notation {
+        Notation::Sci => format_scientific(value, style),
+        Notation::Eng => format_engineering(value, style),
+        // Asking for fixed notation without rounding shows every digit
+        Notation::Fixed if style.precision.is_none() && style.sig_figs.is_none() && style.notation == Notation::Fixed => {
+            value.to_string()
+        }
+        _ => format_fixed(value, style),
+    }
+}
+
+/// The result as a number, or as its parts for mixed units, e.g. `5 ft 10.8 in`.
+/// Every part but the last is whole, so only the last one is rounded, carrying into the others
+/// when it rounds up to a whole one of them.
+pub(crate) fn format_result(conversion: &Conversion, style: &NumberStyle) -> String {
+    let style = &result_style(conversion, style);
+    let Some(last) = conversion.parts.last() else {
+        let result = match exact_result(conversion, style) {
+            Some(exact) => format_exact(exact, style),
+            None => format_number(conversion.result, style),
+        };
+        return style.localize(&result);
+    };
+
+    let parts = Quantity::round_mixed(&conversion.parts, decimals(last.value, style));
+    let Some((last, whole)) = parts.split_last() else {
+        return String::new();
+    };
+    let mut parts: Vec<String> =
+        whole.iter().map(|part| format!("{} {}", style.localize(&part.value.to_string()), part.unit.symbol())).collect();
+    parts.push(format!("{} {}", style.localize(&format_number(last.value, style)), last.unit.symbol()));
+    parts.join(" ")
+}
+
+/// Decimal places a value is rounded to, as many as significant figures call for if given
+fn decimals(value: f64, style: &NumberStyle) -> i32 {
+    match (style.precision, style.sig_figs) {
+        (Some(precision), _) => i32::try_from(precision).unwrap_or(i32::MAX),
+        (None, Some(sig_figs)) if value != 0.0 => {
+            i32::try_from(sig_figs).unwrap_or(i32::MAX).saturating_sub(1 + value.abs().log10().floor() as i32)
+        }
+        _ => DEFAULT_PRECISION as i32,
+    }
+}
+
+/// The style of a result: amounts of money are rounded to the minor units of their currency,
+/// e.g. none for the yen and 3 for the Kuwaiti dinar, unless a rounding is asked for
+fn result_style(conversion: &Conversion, style: &NumberStyle) -> NumberStyle {
+    let minor_units = currency(conversion, &conversion.to_name).and_then(|currency| currency.minor_units);
+    match minor_units {
+        Some(minor_units) if style.precision.is_none() && style.sig_figs.is_none() => {
+            NumberStyle { precision: Some(minor_units.into()), ..style.clone() }
+        }
+        _ => style.clone(),
+    }
+}
+
+/// The currency of a code in a currency conversion, which has its symbol and minor units
+fn currency(conversion: &Conversion, code: &str) -> Option<&'static Currency> {
+    (conversion.category == "currency").then(|| iso::find(code)).flatten()
+}
+
+/// The exact result, when it is to be printed: with `--exact`, or when it is a whole number in
+/// a category of counts such as data storage, which `f64` would round beyond 2^53
+fn exact_result<'a>(conversion: &'a Conversion, style: &NumberStyle) -> Option<&'a BigRational> {
+    let exact = conversion.exact.as_ref()?;
+    if style.exact {
+        return Some(exact);
+    }
+    let rounding = style.precision.is_some() || style.sig_figs.is_some() || style.notation != Notation::Auto;
+    let integral = || registry().dimension(conversion.category).is_some_and(|dimension| dimension.is_integral());
+    (exact.is_integer() && !rounding && integral()).then_some(exact)
+}
+
+/// Writes an exact result as a decimal when it has a finite one and as a fraction such as
+/// `-155/9` otherwise. Fractions too long to read, and results for which a precision or
+/// significant figures are given, are rounded correctly instead.
+pub fn format_exact(value: &BigRational, style: &NumberStyle) -> String {
+    let sig_figs = |sig_figs: usize| exact::to_decimal(value, sig_figs as i32 - 1 - exact::magnitude(value));
+    match (style.precision, style.sig_figs) {
+        (Some(precision), _) => exact::to_decimal(value, precision as i32),
+        (None, Some(n)) => sig_figs(n),
+        (None, None) if exact::is_terminating(value) => exact::to_decimal(value, exact::decimal_places(value)),
+        (None, None) if value.denom().to_string().len() <= MAX_FRACTION_DIGITS => value.to_string(),
+        (None, None) => trim_zeros(sig_figs(EXACT_SIG_FIGS)),
+    }
+}
+
+fn format_fixed(value: f64, style: &NumberStyle) -> String {
+    match (style.precision, style.sig_figs) {
+        (Some(precision), _) => normalize_zero(format!("{:.*}", precision, value)),
+        (None, Some(sig_figs)) => {
+            if value == 0.0 {
+                return format!("{:.*}", sig_figs - 1, 0.0);
+            }
+            let exponent = value.abs().log10().floor() as i32;
+            let decimals = sig_figs as i32 - 1 - exponent;
+            if decimals >= 0 {
+                normalize_zero(format!("{:.*}", decimals as usize, value))
+            } else {
+                let scale = 10f64.powi(-decimals);
+                normalize_zero(format!("{:.0}", (value / scale).round() * scale))
+            }
+        }
+        (None, None) => trim_zeros(normalize_zero(format!("{:.*}", DEFAULT_PRECISION, value))),
+    }
+}
+
+fn format_scientific(value: f64, style: &NumberStyle) -> String {
+    let formatted = match (style.precision, style.sig_figs) {
+        (Some(precision), _) => format!("{:.*e}", precision, value),
+        (None, Some(sig_figs)) => format!("{:.*e}", sig_figs - 1, value),
+        (None, None) => {
+            let formatted = format!("{:.*e}", DEFAULT_PRECISION, value);
+            let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+            format!("{}e{}", trim_zeros(mantissa.to_string()), exponent)
+        }
+    };
+    normalize_zero(formatted)
+}
+
+fn format_engineering(value: f64, style: &NumberStyle) -> String {
+    if value == 0.0 {
+        return format!("{}e0", format_fixed(0.0, style));
+    }
+
+    let mut exponent = (value.abs().log10().floor() as i32).div_euclid(3) * 3;
+    let mut mantissa = format_fixed(value / 10f64.powi(exponent), style);
+
+    // Rounding may carry the mantissa up to 1000, e.g. 999.99996 -> 1000.0000
+    if mantissa.trim_start_matches('-').parse::<f64>().is_ok_and(|m| m >= 1000.0) {
+        exponent += 3;
+        mantissa = format_fixed(value / 10f64.powi(exponent), style);
+    }
+
+    format!("{}e{}", mantissa, exponent)
+}
+
+fn trim_zeros(number: String) -> String {
+    if number.contains('.') {
+        number.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        number
+    }
+}
+
+fn normalize_zero(number: String) -> String {
+    match number.strip_prefix('-') {
+        Some(rest) if rest.chars().all(|c| matches!(c, '0' | '.')) => rest.to_string(),
+        _ => number,
+    }
+}
+
+fn render_text(conversion: &Conversion, style: &NumberStyle) -> String {
+    let mut output = String::new();
+
+    let result = match currency(conversion, &conversion.to_name) {
+        Some(currency) => currency.amount(&format_result(conversion, style)),
+        None if conversion.parts.is_empty() => format!("{} {}", format_result(conversion, style), conversion.to_unit),
+        None => format_result(conversion, style),
+    };
+    let conversion_line = match &conversion.expression {
+        Some(expression) => format!("{} = {}", expression, result),
+        None => format!("{} = {}", format_value(conversion, style), result),
+    };
+    let dashes = "-".repeat(conversion_line.chars().count() + 1);
+
+    output.push_str(&format!("{}\n{}\n{}", dashes, conversion_line, dashes));
+
+    if let Some(date) = conversion.date {
+        output.push_str(&format!("\nas of: {}", date.format("%d %b %Y %H:%M UTC")));
+    }
+
+    output
+}
+
+fn render_text_table(conversions: &[Conversion], style: &NumberStyle) -> String {
+    let Some(first) = conversions.first() else {
+        return String::new();
+    };
+
+    // Amounts of money carry their symbols along, and mixed results their units
+    let (results, units): (Vec<String>, Vec<&str>) = conversions
+        .iter()
+        .map(|conversion| match currency(conversion, &conversion.to_name) {
+            Some(currency) if currency.symbol == currency.code => (format_result(conversion, style), currency.code),
+            Some(currency) => (currency.amount(&format_result(conversion, style)), ""),
+            None if conversion.parts.is_empty() => (format_result(conversion, style), conversion.to_unit.as_str()),
+            None => (format_result(conversion, style), ""),
+        })
+        .unzip();
+    let names: Vec<&str> = conversions
+        .iter()
+        .map(|conversion| currency(conversion, &conversion.to_name).map_or(conversion.to_name.as_str(), |currency| currency.name))
+        .collect();
+    let result_width = results.iter().map(|result| result.chars().count()).max().unwrap_or(0);
+    let unit_width = units.iter().map(|unit| unit.chars().count()).max().unwrap_or(0);
+
+    let title = match &first.expression {
+        Some(expression) => format!("{} =", expression),
+        None => format!("{} =", format_value(first, style)),
+    };
+    let lines: Vec<String> = names
+        .iter()
+        .zip(results.iter().zip(&units))
+        .map(|(name, (result, unit))| {
+            format!(
+                "{:>result_width$} {:<unit_width$}  {}",
+                result, unit, name
+            )
+            .trim_end()
+            .to_string()
+        })
+        .collect();
+
+    let width = lines.iter().chain([&title]).map(|line| line.chars().count()).max().unwrap_or(0);
+    let dashes = "-".repeat(width + 1);
+
+    let mut output = format!("{}\n{}\n{}\n{}\n{}", dashes, title, dashes, lines.join("\n"), dashes);
+    if let Some(date) = first.date {
+        output.push_str(&format!("\nas of: {}", date.format("%d %b %Y %H:%M UTC")));
+    }
+    output
+}
+
+/// The value converted, with its unit, e.g. `10 km` or `€100`
+fn format_value(conversion: &Conversion, style: &NumberStyle) -> String {
+    let value = style.localize(&conversion.value.to_string());
+    match currency(conversion, &conversion.from_name) {
+        Some(currency) => currency.amount(&value),
+        None => format!("{} {}", value, conversion.from_unit),
+    }
+}
+
+fn render_json(conversion: &Conversion, style: &NumberStyle) -> String {
+    // Keep full precision unless rounding was asked for explicitly
+    let result = if style.precision.is_some() || style.sig_figs.is_some() {
+        format_number(conversion.result, style).parse().unwrap_or(conversion.result)
+    } else {
+        conversion.result
+    };
+
+    let mut object = json!({
+        "category": conversion.category,
+        "value": conversion.value.to_f64(),
+        "from": conversion.from_name,
+        "to": conversion.to_name,
+        "result": result,
+        "date": conversion.date.map(|date| date.to_rfc3339()),
+    });
+    if let Some(expression) = &conversion.expression {
+        object["expression"] = json!(expression);
+    }
+    if let Some(exact) = exact_result(conversion, style) {
+        object["exact"] = json!(format_exact(exact, style));
+    }
+    if !conversion.parts.is_empty() {
+        object["parts"] = registry::without_zeros(conversion.parts.clone())
+            .iter()
+            .map(|part| json!({ "value": part.value, "unit": part.unit.symbol() }))
+            .collect();
+    }
+    object.to_string()
+}
+
+fn row(conversion: &Conversion, style: &NumberStyle) -> [String; 6] {
+    [
+        conversion.category.to_string(),
+        style.localize(&conversion.value.to_string()),
+        conversion.from_name.clone(),
+        conversion.to_name.clone(),
+        format_result(conversion, style),
+        conversion.date.map(|date| date.to_rfc3339()).unwrap_or_default(),
+    ]
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rational(numerator: i64, denominator: i64) -> BigRational {
+        BigRational::new(numerator.into(), denominator.into())
+    }
+
+    #[test]
+    fn writes_exact_results() {
+        let style = NumberStyle::default();
+        assert_eq!(format_exact(&rational(3048, 10000), &style), "0.3048");
+        assert_eq!(format_exact(&rational(-155, 9), &style), "-155/9");
+        assert_eq!(format_exact(&rational(1, 3_000_000_007), &style), "0.000000000333333332555556");
+    }
+
+    #[test]
+    fn rounds_exact_results_when_asked() {
+        let precision = NumberStyle { precision: Some(2), ..NumberStyle::default() };
+        assert_eq!(format_exact(&rational(-155, 9), &precision), "-17.22");
+        let sig_figs = NumberStyle { sig_figs: Some(3), ..NumberStyle::default() };
+        assert_eq!(format_exact(&rational(3048, 10000), &sig_figs), "0.305");
+    }
+
+    #[test]
+    fn formats_numbers() {
+        let style = NumberStyle::default();
+        assert_eq!(format_number(62.137119223733, &style), "62.1371");
+        assert_eq!(format_number(1e-9, &style), "1e-9");
+        assert_eq!(format_number(-0.00001, &NumberStyle { notation: Notation::Fixed, precision: Some(2), ..style.clone() }), "0.00");
+        assert_eq!(format_number(999.99996, &NumberStyle { notation: Notation::Eng, ..style }), "1e3");
+    }
+}
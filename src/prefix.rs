@@ -0,0 +1,241 @@
+//! SI and binary prefixes, which make `pm`, `Pm` or `mJ` out of units marked as taking them
+
+use crate::exact::Exact;
+use crate::registry::{System, Unit};
+
+/// Prefixes a unit takes, besides the prefixed units its category already lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Prefixes {
+    None,
+    /// Every SI prefix, from quecto (10^-30) to quetta (10^30)
+    Si,
+    /// SI prefixes from kilo up, and binary (IEC) ones from kibi (2^10) to yobi (2^80), as for bytes
+    Data,
+}
+
+impl Prefixes {
+    /// Note for the list of units
+    pub(crate) fn note(self) -> &'static str {
+        match self {
+            Prefixes::None => "",
+            Prefixes::Si => " (takes SI prefixes)",
+            Prefixes::Data => " (takes SI and binary prefixes)",
+        }
+    }
+
+    fn prefixes(self) -> Vec<&'static Prefix> {
+        match self {
+            Prefixes::None => Vec::new(),
+            Prefixes::Si => SI_PREFIXES.iter().collect(),
+            Prefixes::Data => SI_PREFIXES.iter().filter(|prefix| prefix.factor >= 1e3).chain(BINARY_PREFIXES).collect(),
+        }
+    }
+}
+
+struct Prefix {
+    /// Symbols of the prefix, the micro sign also being written `u`
+    symbols: &'static [&'static str],
+    name: &'static str,
+    factor: f64,
+    exact: Exact,
+    binary: bool,
+}
+
+const fn si(symbols: &'static [&'static str], name: &'static str, factor: f64, exponent: i32) -> Prefix {
+    Prefix { symbols, name, factor, exact: Exact::power_of_ten(exponent), binary: false }
+}
+
+const fn binary(symbols: &'static [&'static str], name: &'static str, power: u32) -> Prefix {
+    let size = 1i128 << (10 * power);
+    Prefix { symbols, name, factor: size as f64, exact: Exact::fraction(size, 1), binary: true }
+}
+
+const SI_PREFIXES: &[Prefix] = &[
+    si(&["q"], "quecto", 1e-30, -30),
+    si(&["r"], "ronto", 1e-27, -27),
+    si(&["y"], "yocto", 1e-24, -24),
+    si(&["z"], "zepto", 1e-21, -21),
+    si(&["a"], "atto", 1e-18, -18),
+    si(&["f"], "femto", 1e-15, -15),
+    si(&["p"], "pico", 1e-12, -12),
+    si(&["n"], "nano", 1e-9, -9),
+    si(&["µ", "u"], "micro", 1e-6, -6),
+    si(&["m"], "milli", 1e-3, -3),
+    si(&["c"], "centi", 1e-2, -2),
+    si(&["d"], "deci", 1e-1, -1),
+    si(&["da"], "deca", 1e1, 1),
+    si(&["h"], "hecto", 1e2, 2),
+    si(&["k"], "kilo", 1e3, 3),
+    si(&["M"], "mega", 1e6, 6),
+    si(&["G"], "giga", 1e9, 9),
+    si(&["T"], "tera", 1e12, 12),
+    si(&["P"], "peta", 1e15, 15),
+    si(&["E"], "exa", 1e18, 18),
+    si(&["Z"], "zetta", 1e21, 21),
+    si(&["Y"], "yotta", 1e24, 24),
+    si(&["R"], "ronna", 1e27, 27),
+    si(&["Q"], "quetta", 1e30, 30),
+];
+
+const BINARY_PREFIXES: &[Prefix] = &[
+    binary(&["Ki"], "kibi", 1),
+    binary(&["Mi"], "mebi", 2),
+    binary(&["Gi"], "gibi", 3),
+    binary(&["Ti"], "tebi", 4),
+    binary(&["Pi"], "pebi", 5),
+    binary(&["Ei"], "exbi", 6),
+    binary(&["Zi"], "zebi", 7),
+    binary(&["Yi"], "yobi", 8),
+];
+
+/// Makes the prefixed units of every unit marked as taking prefixes, e.g. `pm`, `picometre` and
+/// `Pm` out of the metre.
+///
+/// Each prefix goes before the unit's symbol exactly as it is written, so that `mhz` is left to
+/// tell apart `MHz` from `mHz`, and before its names, the symbols before short names such as
+/// `bit`. Aliases a category already has are left out, so `mm` stays the listed millimetre and
+/// `da` is only ever deca, as are lowercase ones it writes in other cases for units of several
+/// sizes, so `kb` stays ambiguous while `pg` is a picogram. A prefixed unit the size of a listed
+/// one takes its name, so that `kB` is a kilobyte.
+pub(crate) fn expand(units: &[Unit]) -> Vec<Unit> {
+    let mut prefixed = Vec::new();
+    for unit in units {
+        let symbol = unit.symbol();
+        let names: Vec<&str> = unit
+            .aliases
+            .iter()
+            .copied()
+            .filter(|alias| !alias.eq_ignore_ascii_case(symbol))
+            .filter(|alias| alias.len() > 1 && alias.bytes().all(|b| b.is_ascii_lowercase() || b == b'-' || b == b'/'))
+            .collect();
+        // Short names such as `bit` or `sec` are written after symbols too, as in `Kibit`
+        let symbols: Vec<&str> = [symbol].into_iter().chain(names.iter().copied().filter(|name| name.len() <= 3)).collect();
+        // A lowercase alias the category writes in other cases for this size and another, like
+        // `kb` for `Kb` and `KB`, is left to case-insensitive matching, which finds it ambiguous
+        let taken = |alias: &str, factor: f64| {
+            let listed: Vec<&Unit> = units.iter().filter(|other| other.dimension == unit.dimension).collect();
+            if listed.iter().any(|other| other.aliases.contains(&alias)) {
+                return true;
+            }
+            let variants = listed.iter().filter(|other| other.aliases.iter().any(|listed| listed.eq_ignore_ascii_case(alias)));
+            let (same, different): (Vec<&&Unit>, Vec<&&Unit>) = variants.partition(|other| same_size(other, factor));
+            !alias.chars().any(char::is_uppercase) && !same.is_empty() && !different.is_empty()
+        };
+
+        for prefix in unit.prefixes.prefixes() {
+            let prefixed_symbols = prefix.symbols.iter().flat_map(|p| symbols.iter().map(move |s| format!("{}{}", p, s)));
+            let prefixed_names = names.iter().map(|name| format!("{}{}", prefix.name, name));
+            let factor = unit.factor * prefix.factor;
+            let aliases: Vec<&'static str> =
+                prefixed_symbols.chain(prefixed_names).filter(|alias| !taken(alias, factor)).map(leak).collect();
+            if aliases.is_empty() {
+                continue;
+            }
+
+            let same = units.iter().find(|other| other.dimension == unit.dimension && same_size(other, factor));
+            let name = same.map_or_else(|| leak(format!("{}{}", capitalize(prefix.name), unit.name.to_lowercase())), |other| other.name);
+            // Written with the listed symbol, so that `auto` gives 500 fm rather than 500 femtometer
+            let aliases: Vec<&'static str> = same.map(Unit::symbol).into_iter().chain(aliases).collect();
+            prefixed.push(Unit {
+                name,
+                aliases: Box::leak(aliases.into_boxed_slice()),
+                factor,
+                systems: systems(units, unit, prefix, factor),
+                exact_factor: unit.exact_factor.map(|exact| exact.times(prefix.exact)),
+                prefixes: Prefixes::None,
+                ..*unit
+            });
+        }
+    }
+    prefixed
+}
+
+fn same_size(unit: &Unit, factor: f64) -> bool {
+    ((unit.factor - factor) / factor).abs() < 1e-12
+}
+
+/// The systems of the unit that a prefixed unit extends, e.g. the picometre below the listed
+/// metric units, so that `auto` can give it. Within the range of a system's listed units, such
+/// as for the kilosecond between minutes and hours, the listed ones read better.
+fn systems(units: &[Unit], unit: &Unit, prefix: &Prefix, factor: f64) -> &'static [System] {
+    let systems: Vec<System> = unit
+        .systems
+        .iter()
+        .copied()
+        .filter(|system| system.is_binary() == prefix.binary)
+        .filter(|system| {
+            let listed = units.iter().filter(|other| other.dimension == unit.dimension && other.systems.contains(system));
+            let (smallest, largest) = listed.fold((f64::INFINITY, 0.0f64), |(smallest, largest), other| {
+                (smallest.min(other.factor), largest.max(other.factor))
+            });
+            factor < smallest * (1.0 - 1e-12) || factor > largest * (1.0 + 1e-12)
+        })
+        .collect();
+    Box::leak(systems.into_boxed_slice())
+}
+
+/// The registry is built once, so the names it makes up live as long as the program
+fn leak(name: String) -> &'static str {
+    Box::leak(name.into_boxed_str())
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::registry::{registry, Quantity};
+
+    fn name(category: &str, alias: &str) -> &'static str {
+        registry().find_in(category, alias).map(|unit| unit.name).unwrap_or_else(|e| panic!("{}: {}", alias, e))
+    }
+
+    #[test]
+    fn prefixes_symbols_and_names() {
+        assert_eq!(name("distance", "pm"), "Picometer");
+        assert_eq!(name("distance", "Pm"), "Petameter");
+        assert_eq!(name("distance", "picometre"), "Picometer");
+        assert_eq!(name("energy", "mJ"), "Millijoule");
+        assert_eq!(name("weight", "pg"), "Picogram");
+        assert_eq!(name("weight", "Pg"), "Petagram");
+        assert_eq!(name("volume", "hL"), "Hectoliter");
+        assert_eq!(name("frequency", "MHz"), "Megahertz");
+        assert_eq!(name("frequency", "mHz"), "Millihertz");
+    }
+
+    #[test]
+    fn prefixes_short_names() {
+        assert_eq!(name("data storage", "Kibit"), "Kibibit");
+        assert_eq!(name("data storage", "Mibit"), "Mebibit");
+        assert_eq!(name("data storage", "kibibit"), "Kibibit");
+        assert_eq!(name("data storage", "kbit"), "Kilobit");
+        assert_eq!(name("time", "msec"), "Millisecond");
+    }
+
+    #[test]
+    fn keeps_listed_units() {
+        assert_eq!(name("data storage", "kB"), "Kilobyte");
+        assert_eq!(name("data storage", "kilobytes"), "Kilobyte");
+        assert_eq!(name("distance", "mm"), "Millimeter");
+    }
+
+    #[test]
+    fn extends_systems() {
+        let auto = |category: &str, value: f64, unit: &str| {
+            let quantity = Quantity::new(value, registry().find_in(category, unit).unwrap()).humanize().unwrap();
+            (quantity.value, quantity.unit.symbol())
+        };
+        assert_eq!(auto("distance", 1.0, "pm"), (1.0, "pm"));
+        assert_eq!(auto("distance", 50.0, "m"), (50.0, "m"));
+        assert_eq!(auto("time", 1_728_000.0, "s"), (20.0, "d"));
+        assert_eq!(auto("data storage", 1e28, "B"), (10.0, "RB"));
+    }
+
+    #[test]
+    fn leaves_case_variants_ambiguous() {
+        assert!(registry().find_in("frequency", "mhz").is_err());
+        assert!(registry().find_in("data storage", "kb").is_err());
+    }
+}
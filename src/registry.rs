@@ -0,0 +1,494 @@
+use num_rational::BigRational;
+use num_traits::{One, ToPrimitive};
+use once_cell::sync::Lazy;
+
+use crate::compound::BaseDimensions;
+use crate::conversions::*;
+use crate::error::Error;
+use crate::exact::{self, Exact};
+use crate::fuzzy;
+use crate::prefix::{self, Prefixes};
+use crate::number::Numeric;
+
+static REGISTRY: Lazy<Registry> = Lazy::new(|| {
+    Registry::new(vec![
+        &distance::Distance,
+        &weight::Weight,
+        &temperature::Temperature,
+        &data_storage::DataStorage,
+        &data_transfer::DataTransfer,
+        &time::Time,
+        &volume::Volume,
+        &area::Area,
+        &frequency::Frequency,
+        &force::Force,
+        &energy::Energy,
+        &power::Power,
+        &speed::Speed,
+    ])
+});
+
+/// The registry of every measurement category with fixed units.
+///
+/// Currencies are not part of it, their units depend on downloaded rates.
+pub fn registry() -> &'static Registry {
+    &REGISTRY
+}
+
+/// A measurement category such as distance or time
+pub trait Dimension: Sync {
+    /// Human readable name, e.g. "distance"
+    fn name(&self) -> &'static str;
+    /// Name of the subcommand, e.g. "dist"
+    fn command(&self) -> &'static str;
+    fn base_dimensions(&self) -> BaseDimensions;
+    fn units(&self) -> Vec<Unit>;
+    fn help_text(&self) -> String;
+    /// Whether every unit is a whole number of the base unit, as bytes are of bits, so that whole
+    /// results are exact however large they get
+    fn is_integral(&self) -> bool {
+        self.units()
+            .iter()
+            .all(|unit| unit.exact_factor().is_some_and(|factor| factor.is_integer()) && unit.zero == 0.0)
+    }
+}
+
+/// A family of units that `auto` picks a readable unit from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum System {
+    /// SI units with decimal prefixes and the common units next to them, e.g. mm, m, km or s, min, h
+    Metric,
+    /// Imperial and US customary units, e.g. in, ft, mi
+    Imperial,
+    /// Bytes with decimal prefixes, e.g. B, kB, MB
+    DecimalBytes,
+    /// Bytes with binary (IEC) prefixes, e.g. B, KiB, MiB
+    BinaryBytes,
+    /// Bits with decimal prefixes, e.g. b, Kb, Mb
+    DecimalBits,
+    /// Bits with binary (IEC) prefixes, e.g. b, Kib, Mib
+    BinaryBits,
+}
+
+impl System {
+    /// Whether the system steps in binary (IEC) prefixes
+    pub(crate) fn is_binary(self) -> bool {
+        matches!(self, System::BinaryBytes | System::BinaryBits)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Unit {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    /// Name of the dimension the unit belongs to
+    pub dimension: &'static str,
+    pub(crate) factor: f64,
+    pub(crate) zero: f64,
+    /// Size of the dimension's base unit in coherent SI units, e.g. 1e-3 for grams
+    pub(crate) si_scale: f64,
+    pub(crate) base: BaseDimensions,
+    /// Systems the unit belongs to, the first one being the one `auto` stays in
+    pub(crate) systems: &'static [System],
+    /// `factor` and `zero` as exact fractions, `None` when they are measured or rounded
+    pub(crate) exact_factor: Option<Exact>,
+    pub(crate) exact_zero: Option<Exact>,
+    /// Whether the unit measures temperature differences, which convert without the offsets
+    pub(crate) difference: bool,
+    /// Prefixes the unit takes besides the prefixed units listed with it
+    pub(crate) prefixes: Prefixes,
+}
+
+impl Unit {
+    /// The shortest way of writing the unit, e.g. "km"
+    pub fn symbol(&self) -> &'static str {
+        self.aliases.first().copied().unwrap_or(self.name)
+    }
+
+    pub fn to_base(&self, value: f64) -> f64 {
+        (value - self.zero) * self.factor
+    }
+
+    pub fn from_base(&self, value: f64) -> f64 {
+        value / self.factor + self.zero
+    }
+
+    /// Size of one unit in coherent SI units, ignoring any zero offset. The exact factor is used
+    /// where there is one, as the `f64` one may be rounded, e.g. 453.592 g for the pound.
+    pub fn si_factor(&self) -> f64 {
+        let factor = self.exact_factor.and_then(|exact| exact.to_rational().to_f64()).unwrap_or(self.factor);
+        factor * self.si_scale
+    }
+
+    pub fn base_dimensions(&self) -> BaseDimensions {
+        self.base
+    }
+
+    pub fn systems(&self) -> &'static [System] {
+        self.systems
+    }
+
+    /// Size of one unit in the category's base unit as an exact fraction, if it is defined as one
+    pub fn exact_factor(&self) -> Option<BigRational> {
+        self.exact_factor.map(Exact::to_rational)
+    }
+
+    /// Converts a value of this unit to `unit` of the same dimension, in any kind of number.
+    ///
+    /// Gives `None` when the units can't be held in that kind of number, such as a measured
+    /// factor as a [`BigRational`].
+    pub fn convert<N: Numeric>(&self, value: N, unit: &Unit) -> Option<N> {
+        // A difference of 10 °C is one of 18 °F, whichever side it is written on
+        let (from, to) = if self.difference || unit.difference {
+            (self.as_difference(), unit.as_difference())
+        } else {
+            (*self, *unit)
+        };
+        let base = (value - N::zero(&from)?) * N::factor(&from)?;
+        Some(base / N::factor(&to)? + N::zero(&to)?)
+    }
+
+    /// The unit as a measure of differences, without the offset of its zero, e.g. a degree
+    /// Fahrenheit of change
+    pub fn as_difference(&self) -> Unit {
+        Unit { zero: 0.0, exact_zero: Some(Exact::fraction(0, 1)), difference: true, ..*self }
+    }
+
+    pub fn is_difference(&self) -> bool {
+        self.difference
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantity {
+    pub value: f64,
+    pub unit: Unit,
+}
+
+impl Quantity {
+    pub fn new(value: f64, unit: Unit) -> Self {
+        Quantity { value, unit }
+    }
+
+    pub fn to(&self, unit: Unit) -> Result<Quantity, Error> {
+        if self.unit.dimension != unit.dimension {
+            return Err(Error::IncompatibleUnits {
+                from: self.unit.name.to_string(),
+                to: unit.name.to_string(),
+            });
+        }
+        let value = self.unit.convert(self.value, &unit).expect("f64 holds every unit");
+        Ok(Quantity::new(value, unit))
+    }
+
+    /// Converts with exact fractions, e.g. 1 ft is exactly 0.3048 m and 1 °F is -155/9 °C.
+    ///
+    /// The value is read as the decimal it is written as. Gives `None` when either unit has a
+    /// measured or rounded factor, such as the light-year, whose result is only ever approximate.
+    pub fn to_exact(&self, unit: Unit) -> Result<Option<BigRational>, Error> {
+        self.to(unit)?;
+        Ok(exact::value(self.value).and_then(|value| self.unit.convert(value, &unit)))
+    }
+
+    /// Converts to the unit of the same system that reads best: the largest one in which the
+    /// value is at least 1, e.g. 123456789 B gives 123.4568 MB and 1536 KiB gives 1.5 MiB.
+    ///
+    /// Units outside of any system, such as the parsec, are kept.
+    pub fn humanize(&self) -> Result<Quantity, Error> {
+        let Some(system) = self.unit.systems.first() else {
+            return Ok(*self);
+        };
+        if self.value == 0.0 || !self.value.is_finite() {
+            return Ok(*self);
+        }
+
+        let mut candidates: Vec<Unit> = registry()
+            .known_units_of(self.unit.dimension)
+            .filter(|unit| unit.systems.contains(system))
+            .copied()
+            .collect();
+        candidates.sort_by(|a, b| b.factor.total_cmp(&a.factor));
+
+        for unit in &candidates {
+            let quantity = self.to(*unit)?;
+            // Leave room for rounding, 1 mi in ft and back may be 0.9999999999999999
+            if quantity.value.abs() >= 1.0 - 1e-9 {
+                return Ok(quantity);
+            }
+        }
+        match candidates.last() {
+            Some(smallest) => self.to(*smallest),
+            None => Ok(*self),
+        }
+    }
+
+    /// Splits the value over several units, largest first, e.g. 5.9 ft into 5 ft and 10.8 in.
+    ///
+    /// All parts but the last are whole numbers. Parts that are zero are left out, unless all are.
+    /// Units defined exactly are split exactly, so that 10 st is 140 lb and not a hair more as
+    /// their rounded `f64` factors would have it. The last part may still need rounding, see
+    /// [`Quantity::round_mixed`].
+    pub fn to_mixed(&self, units: &[Unit]) -> Result<Vec<Quantity>, Error> {
+        Ok(without_zeros(self.split_mixed(units)?))
+    }
+
+    /// The parts of [`Quantity::to_mixed`], one per unit even when zero, so that rounding can
+    /// carry into them
+    pub(crate) fn split_mixed(&self, units: &[Unit]) -> Result<Vec<Quantity>, Error> {
+        let mut units = units.to_vec();
+        units.sort_by(|a, b| b.factor.total_cmp(&a.factor));
+        for unit in &units {
+            self.to(*unit)?;
+        }
+        let Some((last, larger)) = units.split_last() else {
+            return Ok(Vec::new());
+        };
+
+        let parts = match self.split_exactly(&units) {
+            Some(parts) => parts,
+            None => {
+                let mut parts = Vec::new();
+                let mut rest = Quantity::new(self.value.abs(), self.unit);
+                for unit in larger {
+                    let quantity = rest.to(*unit)?;
+                    // Leave room for rounding, 5.9 ft less 5 ft may be 10.799999999999997 in
+                    let whole = (quantity.value * (1.0 + 1e-12)).floor();
+                    parts.push(Quantity::new(whole, *unit));
+                    rest = Quantity::new((quantity.value - whole).max(0.0), *unit);
+                }
+                parts.push(rest.to(*last)?);
+                parts
+            }
+        };
+        Ok(signed(parts, self.value < 0.0))
+    }
+
+    /// The parts of [`Quantity::to_mixed`] in exact arithmetic, if every unit is defined exactly
+    fn split_exactly(&self, units: &[Unit]) -> Option<Vec<Quantity>> {
+        let (last, larger) = units.split_last()?;
+        let mut parts = Vec::new();
+        let (mut unit, mut rest) = (self.unit, exact::value(self.value.abs())?);
+        for larger in larger {
+            let value = unit.convert(rest, larger)?;
+            let whole = value.floor();
+            parts.push(Quantity::new(whole.to_f64()?, *larger));
+            (unit, rest) = (*larger, value - whole);
+        }
+        parts.push(Quantity::new(unit.convert(rest, last)?.to_f64()?, *last));
+        Some(parts)
+    }
+
+    /// Rounds the last of the parts of a mixed quantity to `decimals` places. When it rounds up to
+    /// a whole one of the unit before it, that is carried, so that 5 ft 11.99999 in gives 6 ft at
+    /// 4 places, and when it rounds to zero it is left out. Carrying only reaches the units among
+    /// the parts, so that 59 min 59.99999 s only gives 1 h if a zero part in hours comes first.
+    pub fn round_mixed(parts: &[Quantity], decimals: i32) -> Vec<Quantity> {
+        let negative = parts.iter().any(|part| part.value < 0.0);
+        let mut parts: Vec<Quantity> = parts.iter().map(|part| Quantity::new(part.value.abs(), part.unit)).collect();
+        let scale = 10f64.powi(decimals);
+        // Beyond the precision of `f64` there is nothing to round
+        let round = |value: f64| if scale.is_finite() && scale > 0.0 { (value * scale).round() / scale } else { value };
+        if let Some(last) = parts.last_mut() {
+            last.value = round(last.value);
+        }
+
+        for i in (1..parts.len()).rev() {
+            let whole = ratio(parts[i - 1].unit, parts[i].unit);
+            if parts[i].value < round(whole) {
+                break;
+            }
+            let rest = (parts[i].value - whole).max(0.0);
+            parts[i].value = if i == parts.len() - 1 { round(rest) } else { rest.round() };
+            parts[i - 1].value += 1.0;
+        }
+        signed(without_zeros(parts), negative)
+    }
+    /// Converts to each of `units`, sorted by the magnitude of the results
+    pub fn to_many(&self, units: impl IntoIterator<Item = Unit>) -> Result<Vec<Quantity>, Error> {
+        let mut quantities = units.into_iter().map(|unit| self.to(unit)).collect::<Result<Vec<_>, _>>()?;
+        quantities.sort_by(|a, b| a.value.abs().total_cmp(&b.value.abs()));
+        Ok(quantities)
+    }
+}
+
+/// How many of `smaller` make one `larger`, exactly where both are defined exactly
+fn ratio(larger: Unit, smaller: Unit) -> f64 {
+    let one = BigRational::one();
+    larger
+        .convert(one, &smaller)
+        .and_then(|ratio| ratio.to_f64())
+        .unwrap_or_else(|| larger.convert(1.0, &smaller).unwrap_or(f64::INFINITY))
+}
+
+/// Leaves out parts that are zero, unless all are
+pub(crate) fn without_zeros(parts: Vec<Quantity>) -> Vec<Quantity> {
+    let last = parts.len().saturating_sub(1);
+    let mut parts: Vec<Quantity> = parts
+        .into_iter()
+        .enumerate()
+        .filter(|(i, part)| part.value != 0.0 || *i == last)
+        .map(|(_, part)| part)
+        .collect();
+    if parts.len() > 1 && parts[parts.len() - 1].value == 0.0 {
+        parts.pop();
+    }
+    parts
+}
+
+/// Gives the first part that isn't zero the sign of the whole quantity
+fn signed(mut parts: Vec<Quantity>, negative: bool) -> Vec<Quantity> {
+    let first = parts.iter().position(|part| part.value != 0.0).unwrap_or(0);
+    if let (Some(first), true) = (parts.get_mut(first), negative) {
+        first.value = -first.value;
+    }
+    parts
+}
+
+pub struct Registry {
+    dimensions: Vec<&'static dyn Dimension>,
+    units: Vec<Unit>,
+    /// Units made of a prefix and a unit taking prefixes, such as `pm`, which are found by their
+    /// aliases but not listed
+    prefixed: Vec<Unit>,
+}
+
+impl Registry {
+    fn new(dimensions: Vec<&'static dyn Dimension>) -> Self {
+        let units: Vec<Unit> = dimensions.iter().flat_map(|dimension| dimension.units()).collect();
+        let prefixed = prefix::expand(&units);
+        Registry { dimensions, units, prefixed }
+    }
+
+    pub fn dimensions(&self) -> impl Iterator<Item = &'static dyn Dimension> + '_ {
+        self.dimensions.iter().copied()
+    }
+
+    /// Looks up a dimension by its name or subcommand
+    pub fn dimension(&self, name: &str) -> Option<&'static dyn Dimension> {
+        self.dimensions()
+            .find(|dimension| dimension.name() == name || dimension.command() == name)
+    }
+
+    pub fn units(&self) -> impl Iterator<Item = &Unit> {
+        self.units.iter()
+    }
+
+    pub fn units_of(&self, dimension: &str) -> impl Iterator<Item = &Unit> {
+        let dimension = self.dimension(dimension).map(|dimension| dimension.name());
+        self.units.iter().filter(move |unit| Some(unit.dimension) == dimension)
+    }
+
+    /// Every unit that can be looked up: the listed ones, then the prefixed ones
+    fn known_units(&self) -> impl Iterator<Item = &Unit> {
+        self.units.iter().chain(&self.prefixed)
+    }
+
+    fn known_units_of(&self, dimension: &str) -> impl Iterator<Item = &Unit> {
+        let dimension = self.dimension(dimension).map(|dimension| dimension.name());
+        self.known_units().filter(move |unit| Some(unit.dimension) == dimension)
+    }
+
+    /// Finds the first unit with the given alias in any dimension, or else the one unit whose
+    /// alias matches ignoring case and accents, as `Kelvin` or `romer` do
+    pub fn find(&self, alias: &str) -> Option<Unit> {
+        self.known_units()
+            .find(|unit| unit.aliases.contains(&alias))
+            .or_else(|| fuzzy::unique(self.known_units().map(|unit| (unit, unit.aliases)), alias))
+            .copied()
+    }
+
+    /// Finds the unit with the given alias within one dimension, also ignoring case and accents
+    /// where that leaves a single unit
+    pub fn find_in(&self, dimension: &str, alias: &str) -> Result<Unit, Error> {
+        if let Some(unit) = self.find_exactly_in(dimension, alias) {
+            return Ok(unit);
+        }
+        if let Some(unit) = fuzzy::unique(self.known_units_of(dimension).map(|unit| (unit, unit.aliases)), alias) {
+            return Ok(*unit);
+        }
+
+        let category = self.dimension(dimension).map_or("unknown", |dimension| dimension.name());
+        let aliases = self.units_of(dimension).flat_map(|unit| unit.aliases.iter().copied());
+        let elsewhere = self.find(alias).and_then(|unit| self.dimension(unit.dimension));
+        Err(Error::unknown_unit(category, alias, fuzzy::suggest(aliases, alias)).elsewhere(elsewhere))
+    }
+
+    fn find_exactly_in(&self, dimension: &str, alias: &str) -> Option<Unit> {
+        self.known_units_of(dimension).find(|unit| unit.aliases.contains(&alias)).copied()
+    }
+
+    /// Finds the one dimension in which both units are defined. Units written exactly as
+    /// defined are looked for first, so that `c` is Celsius even though `C` is another unit.
+    pub fn detect(&self, from: &str, to: &str) -> Result<&'static dyn Dimension, Error> {
+        let mut candidates: Vec<_> = self
+            .dimensions()
+            .filter(|dimension| {
+                self.find_exactly_in(dimension.name(), from).is_some()
+                    && self.find_exactly_in(dimension.name(), to).is_some()
+            })
+            .collect();
+        if candidates.is_empty() {
+            candidates = self
+                .dimensions()
+                .filter(|dimension| {
+                    self.find_in(dimension.name(), from).is_ok() && self.find_in(dimension.name(), to).is_ok()
+                })
+                .collect();
+        }
+
+        match candidates.as_slice() {
+            [dimension] => Ok(*dimension),
+            [] => {
+                for alias in [from, to] {
+                    if self.find(alias).is_none() {
+                        let aliases = self.units().flat_map(|unit| unit.aliases.iter().copied());
+                        return Err(Error::unknown_unit("", alias, fuzzy::suggest(aliases, alias)));
+                    }
+                }
+                Err(Error::IncompatibleUnits { from: from.to_string(), to: to.to_string() })
+            }
+            _ => Err(Error::AmbiguousUnits {
+                from: from.to_string(),
+                to: to.to_string(),
+                candidates: candidates.iter().map(|dimension| dimension.command()).collect(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn units(category: &str, aliases: &[&str]) -> Vec<Unit> {
+        aliases.iter().map(|alias| registry().find_in(category, alias).unwrap()).collect()
+    }
+
+    fn mixed(category: &str, value: f64, from: &str, to: &[&str], decimals: i32) -> Vec<(f64, &'static str)> {
+        let quantity = Quantity::new(value, registry().find_in(category, from).unwrap());
+        let parts = quantity.split_mixed(&units(category, to)).unwrap();
+        Quantity::round_mixed(&parts, decimals).iter().map(|part| (part.value, part.unit.symbol())).collect()
+    }
+
+    #[test]
+    fn splits_over_units() {
+        assert_eq!(mixed("distance", 5.9, "ft", &["ft", "in"], 4), [(5.0, "ft"), (10.8, "in")]);
+        assert_eq!(mixed("time", 3725.0, "s", &["h", "min", "s"], 4), [(1.0, "h"), (2.0, "min"), (5.0, "s")]);
+        assert_eq!(mixed("distance", -5.9, "ft", &["in", "ft"], 4), [(-5.0, "ft"), (10.8, "in")]);
+    }
+
+    #[test]
+    fn splits_exactly_defined_units_exactly() {
+        assert_eq!(mixed("weight", 10.0, "st", &["lb", "oz"], 4), [(140.0, "lb")]);
+        let parts = Quantity::new(10.0, units("weight", &["st"])[0]).to_mixed(&units("weight", &["lb", "oz"])).unwrap();
+        assert_eq!(parts.len(), 1);
+    }
+
+    #[test]
+    fn carries_rounded_parts() {
+        assert_eq!(mixed("distance", 5.99999999, "ft", &["ft", "in"], 4), [(6.0, "ft")]);
+        assert_eq!(mixed("time", 3599.99999, "s", &["h", "min", "s"], 4), [(1.0, "h")]);
+        assert_eq!(mixed("distance", -0.5, "ft", &["ft", "in"], 4), [(-6.0, "in")]);
+        assert_eq!(mixed("time", 0.0, "s", &["h", "min"], 4), [(0.0, "min")]);
+    }
+}
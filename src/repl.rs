@@ -0,0 +1,291 @@
+use std::path::PathBuf;
+
+use directories::ProjectDirs;
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+
+use crate::conversions::currency;
+use crate::error::Error;
+use crate::output::{self, Format, NumberStyle};
+use crate::registry::registry;
+use crate::{Cmd, Outcome};
+
+const PROMPT: &str = "cnv> ";
+
+const REPL_COMMANDS: [&str; 4] = [":list", ":help", ":quit", "ans"];
+
+const HELP: &str = "Type a conversion as on the command line, e.g.
+  10 km mi
+  temp 100 c f
+  5 ft + 3 in to cm
+`ans` stands for the previous result, e.g. `ans to m` or `ans * 2`.
+
+Commands:
+  :list            List the categories
+  :list <CATEGORY> List the units of a category, e.g. :list temp
+  :help            Show this help
+  :quit            Leave the session (or press Ctrl-D)";
+
+/// Runs the interactive session until the input ends or `:quit` is entered.
+///
+/// `format` and `style` are the defaults for every line; options given on a line override them.
+pub fn run(format: Format, style: &NumberStyle) -> Result<(), Error> {
+    let mut editor: Editor<UnitCompleter, DefaultHistory> =
+        Editor::new().map_err(|e| Error::Io(format!("can't start the line editor: {}", e)))?;
+    editor.set_helper(Some(UnitCompleter::new()));
+
+    let history = history_path();
+    if let Some(path) = &history {
+        // A missing history file just means this is the first session
+        let _ = editor.load_history(path);
+    }
+
+    let mut session = Session { format, style: style.clone(), ans: None, header_printed: false, warned: None };
+    loop {
+        let line = match editor.readline(PROMPT) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(Error::Io(e.to_string())),
+        };
+
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+
+        match session.eval(line) {
+            Ok(Some(output)) => println!("{}", output),
+            Ok(None) => break,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    if let Some(path) = &history {
+        editor
+            .save_history(path)
+            .map_err(|e| Error::Io(format!("can't save history: {}", e)))?;
+    }
+    Ok(())
+}
+
+fn history_path() -> Option<PathBuf> {
+    let dirs = ProjectDirs::from("", "", "cnv")?;
+    let data_dir = dirs.data_dir();
+    std::fs::create_dir_all(data_dir).ok()?;
+    Some(data_dir.join("history.txt"))
+}
+
+struct Session {
+    format: Format,
+    style: NumberStyle,
+    /// The previous result and its unit
+    ans: Option<(f64, String)>,
+    header_printed: bool,
+    /// The last warning shown, so that stale exchange rates are mentioned once
+    warned: Option<String>,
+}
+
+impl Session {
+    /// Evaluates one line, returning what to print or `None` to end the session
+    fn eval(&mut self, line: &str) -> Result<Option<String>, String> {
+        let mut args = split_args(line);
+        match args.first().map(String::as_str) {
+            Some(":quit" | ":q" | ":exit") => return Ok(None),
+            Some(":help") => return Ok(Some(HELP.to_string())),
+            Some(":list") => return list(args.get(1).map(String::as_str)).map(Some),
+            Some(command) if command.starts_with(':') => {
+                return Err(format!("Unknown command '{}', see :help", command));
+            }
+            _ => {}
+        }
+
+        while let Some(position) = args.iter().position(|arg| arg == "ans") {
+            let (value, unit) = self.ans.clone().ok_or("There is no previous result yet")?;
+            let mut replacement = vec![self.style.locale().format(&value.to_string(), false)];
+            if !unit.is_empty() {
+                replacement.push(unit);
+            }
+            args.splice(position..position + 1, replacement);
+        }
+
+        let mut cmd = Cmd::try_parse_args(std::iter::once("cnv".to_string()).chain(args)).map_err(|e| e.to_string())?;
+        let (format, style) = line_options(&cmd, self.format, &self.style);
+        // Values on the line are read in the session's locale unless the line gives its own
+        cmd.style = style.clone();
+
+        let mut output = String::new();
+        if let (Some(header), false) = (format.header(), self.header_printed) {
+            output.push_str(&header);
+            output.push('\n');
+        }
+
+        let outcome = cmd.execute().map_err(|e| e.to_string())?;
+        if let Some(warning) = outcome.warning().filter(|warning| self.warned.as_deref() != Some(*warning)) {
+            eprintln!("Warning: {}", warning);
+            self.warned = Some(warning.to_string());
+        }
+        match outcome {
+            Outcome::Conversion(conversion) => {
+                output.push_str(&output::render(&conversion, format, &style));
+                // A mixed result is remembered in its largest unit
+                let unit = conversion.parts.first().map_or(conversion.to_unit, |part| part.unit.symbol().to_string());
+                self.ans = Some((conversion.result, unit));
+            }
+            Outcome::Table(conversions) => output.push_str(&output::render_table(&conversions, format, &style)),
+            Outcome::UnitList(list) => return Ok(Some(list)),
+            Outcome::Repl => return Err("Already in an interactive session".to_string()),
+            Outcome::Batch { .. } | Outcome::Csv(_) => {
+                return Err("Files can not be converted in an interactive session".to_string());
+            }
+        }
+        self.header_printed |= format.header().is_some();
+        Ok(Some(output))
+    }
+}
+
+/// The output options for one line: its own when given, the session's otherwise
+pub(crate) fn line_options(cmd: &Cmd, format: Format, style: &NumberStyle) -> (Format, NumberStyle) {
+    let format = if cmd.format == Format::default() { format } else { cmd.format };
+    let style = if cmd.style.precision.is_none()
+        && cmd.style.sig_figs.is_none()
+        && cmd.style.notation == Default::default()
+        && !cmd.style.exact
+        && cmd.style.locale.is_none()
+        && !cmd.style.group
+    {
+        style.clone()
+    } else {
+        cmd.style.clone()
+    };
+    (format, style)
+}
+
+fn list(category: Option<&str>) -> Result<String, String> {
+    match category {
+        None => {
+            let mut text = String::from("Categories:\n");
+            for dimension in registry().dimensions() {
+                text.push_str(&format!("- {} ({})\n", dimension.command(), dimension.name()));
+            }
+            text.push_str("- currency (currency)");
+            Ok(text)
+        }
+        Some("currency") => currency::help_text(None).map_err(|e| e.to_string()),
+        Some(category) => registry()
+            .dimension(category)
+            .map(|dimension| dimension.help_text())
+            .ok_or_else(|| format!("Unknown category '{}', see :list", category)),
+    }
+}
+
+/// Splits a line into arguments like a shell would, so that `'fl oz'` stays one argument
+pub(crate) fn split_args(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut in_arg = false;
+
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '\'' || c == '"' => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            None if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+
+    args
+}
+
+/// Completes unit aliases, category subcommands and session commands
+struct UnitCompleter {
+    words: Vec<String>,
+}
+
+impl UnitCompleter {
+    fn new() -> Self {
+        let registry = registry();
+        let mut words: Vec<String> = registry.units().flat_map(|unit| unit.aliases.iter()).map(|alias| alias.to_string()).collect();
+        words.extend(registry.dimensions().map(|dimension| dimension.command().to_string()));
+        words.push("currency".to_string());
+        words.extend(REPL_COMMANDS.iter().map(|command| command.to_string()));
+        words.sort();
+        words.dedup();
+        UnitCompleter { words }
+    }
+}
+
+impl Completer for UnitCompleter {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let start = word_start(&line[..pos]);
+        let prefix = &line[start..pos];
+
+        let candidates = self
+            .words
+            .iter()
+            .filter(|word| word.starts_with(prefix))
+            .map(|word| {
+                // Aliases of several words, such as "fl oz", have to stay one argument
+                let replacement = if word.contains(' ') { format!("'{}'", word) } else { word.clone() };
+                Pair { display: word.clone(), replacement }
+            })
+            .collect();
+        Ok((start, candidates))
+    }
+}
+
+/// Where the word being typed at the end of `line` starts, after a space of any width or `(`
+fn word_start(line: &str) -> usize {
+    line.char_indices()
+        .rev()
+        .find(|&(_, c)| c.is_whitespace() || c == '(')
+        .map_or(0, |(i, c)| i + c.len_utf8())
+}
+
+impl Hinter for UnitCompleter {
+    type Hint = String;
+}
+
+impl Highlighter for UnitCompleter {}
+
+impl Validator for UnitCompleter {}
+
+impl Helper for UnitCompleter {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_word_being_typed() {
+        assert_eq!(word_start("10 k"), 3);
+        assert_eq!(word_start("km"), 0);
+        assert_eq!(word_start("1 (kg"), 3);
+        // U+3000, the ideographic space, is three bytes long
+        assert_eq!(word_start("10\u{3000}km"), 5);
+    }
+}